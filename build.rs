@@ -0,0 +1,22 @@
+use std::process::Command;
+
+// Stamp the git commit into the binary for the /version endpoint. CI can pass
+// it in as GIT_SHA when building outside a git checkout.
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+
+    let sha = std::env::var("GIT_SHA").ok().or_else(|| {
+        Command::new("git")
+            .args(["rev-parse", "--short=12", "HEAD"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|sha| sha.trim().to_string())
+    });
+
+    // Not PINGPAIR_*: that prefix is read as settings, see config.rs
+    println!("cargo:rustc-env=BUILD_GIT_SHA={}", sha.unwrap_or_else(|| "unknown".to_string()));
+}
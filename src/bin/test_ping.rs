@@ -1,7 +1,9 @@
-// Using a direct path to the function instead of crate-related paths
+// Uses the library's ping function so it shares the country catalogue
+use PingPair::commands::pingpair::simulate_ping_time;
+
 fn main() {
     // Get the ping function directly
-    let result = crate_ping();
+    let result = simulate_ping_time();
     
     // Print the result
     println!("Ping command output:\n{}", result);
@@ -14,44 +16,3 @@ fn main() {
     
     println!("Test passed successfully!");
 }
-
-// Copy of the ping function to avoid import issues
-fn crate_ping() -> String {
-    // Get available countries
-    let spotlights = vec![
-        ("Kenya", "🇰🇪", vec!["Home to over 40 ethnic groups", "Birthplace of marathon champions", "Famous for wildlife safaris"]),
-        ("India", "🇮🇳", vec!["World's largest democracy", "Home to Bollywood", "Known for diverse cuisine and spices"]),
-        ("Brazil", "🇧🇷", vec!["Famous for Carnival and samba", "Home to most of the Amazon rainforest", "Soccer is a national passion"]),
-    ];
-    
-    // Select a random country
-    let spotlight_index = (std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as usize) % spotlights.len();
-    let (country, emoji, facts) = &spotlights[spotlight_index];
-    
-    // Format facts as bullet points
-    let facts_formatted = facts.iter()
-        .map(|fact| format!("- {}", fact))
-        .collect::<Vec<String>>()
-        .join("\n");
-    
-    format!(r#"
-# It's Ping Time! 🌍
-
-### Global Spotlight: {} {}
-
-**Fun Facts:**
-{}
-
-Would you love to meet someone from {} or someone interested in exploring {}?
-
-Reply with `yes` to be matched with someone for a cultural exchange!
-    "#, 
-    country, 
-    emoji,
-    facts_formatted,
-    country,
-    country)
-} 
\ No newline at end of file
@@ -3,17 +3,27 @@ use oc_bots_sdk::api::command::SuccessResult;
 use oc_bots_sdk::api::command::EphemeralMessageBuilder;
 use oc_bots_sdk::api::definition::*;
 use oc_bots_sdk::oc_api::client::Client;
-use oc_bots_sdk::types::{BotCommandContext, MessageContentInitial, MessageId, TextContent};
+use oc_bots_sdk::types::{BotCommandContext, BotCommandScope, Chat, MessageContentInitial, MessageId, TextContent};
 use oc_bots_sdk_offchain::AgentRuntime;
+use std::cell::Cell;
 use std::sync::LazyLock;
 use async_trait::async_trait;
+use tracing::info;
 
-use crate::api;
+use crate::{api, model};
+use crate::model::i18n::DEFAULT_LOCALE;
+use crate::model::spotlight::DEFAULT_COMMUNITY;
 
 pub struct PingPairCommand;
 
 static DEFINITION: LazyLock<BotCommandDefinition> = LazyLock::new(PingPairCommand::definition);
 
+tokio::task_local! {
+    // Metric label of the subcommand being executed, set for the server to
+    // read once the command returns
+    pub static SUBCOMMAND: Cell<&'static str>;
+}
+
 #[async_trait]
 impl CommandHandler<AgentRuntime> for PingPairCommand {
     fn definition(&self) -> &BotCommandDefinition {
@@ -27,21 +37,24 @@ impl CommandHandler<AgentRuntime> for PingPairCommand {
         let ctx = oc_client.context();
         let user_id = ctx.command.initiator.to_string();
         let username = "user"; // Default placeholder since we can't get the actual username
-        let args = &ctx.command.args;
+        let subcommand = ctx.command.arg::<String>("subcommand").to_string();
+        let extra = ctx.command.arg::<String>("args").to_string();
+        let community = community_of(ctx);
+        let label = api::handlers::subcommand_label(&subcommand);
+        let _ = SUBCOMMAND.try_with(|current| current.set(label));
+        info!(subcommand = label, community = %community, "Running pingpair command");
+
+        // Handlers expect the subcommand followed by its whitespace-separated arguments
+        let args: Vec<String> = std::iter::once(subcommand)
+            .chain(extra.split_whitespace().map(String::from))
+            .collect();
         
-        let result = if args.is_empty() {
-            api::handlers::get_help_menu()
-        } else {
-            let subcommand = &args[0].name;
-            
-            match subcommand.as_str() {
-                "start" => api::handlers::handle_start_command(user_id.clone(), username.to_string()),
-                "profile" => api::handlers::handle_profile_command(user_id.clone(), username.to_string(), args.iter().map(|a| a.name.clone()).collect()),
-                "skip" => api::handlers::handle_skip_command(user_id.clone(), username.to_string()),
-                "stats" => api::handlers::handle_stats_command(user_id.clone(), username.to_string()),
-                "timezone" => api::handlers::handle_timezone_command(user_id.clone(), username.to_string(), args.iter().map(|a| a.name.clone()).collect()),
-                "ping" => simulate_ping_time(),
-                _ => "Unknown subcommand. Try `/pingpair` for help.".to_string(),
+        let result = match api::rate_limit::limiter().check(&user_id, &chat_of(ctx), label) {
+            Ok(()) => api::handlers::dispatch(user_id, username.to_string(), args, &community),
+            Err(retry_after_secs) => {
+                info!(subcommand = label, retry_after_secs, "Rate limited");
+                let locale = model::state::get_user_locale(&user_id);
+                api::handlers::get_rate_limited_message(&community, &locale, retry_after_secs)
             }
         };
         
@@ -101,10 +114,74 @@ impl PingPairCommand {
                                 name: "Timezone".to_string(),
                                 value: "timezone".to_string(),
                             },
+                            CommandOptionChoiceString {
+                                name: "Language Exchange".to_string(),
+                                value: "exchange".to_string(),
+                            },
+                            CommandOptionChoiceString {
+                                name: "Bot Language".to_string(),
+                                value: "language".to_string(),
+                            },
+                            CommandOptionChoiceString {
+                                name: "Map".to_string(),
+                                value: "map".to_string(),
+                            },
+                            CommandOptionChoiceString {
+                                name: "Pick".to_string(),
+                                value: "pick".to_string(),
+                            },
+                            CommandOptionChoiceString {
+                                name: "Pair".to_string(),
+                                value: "pair".to_string(),
+                            },
+                            CommandOptionChoiceString {
+                                name: "Unpair".to_string(),
+                                value: "unpair".to_string(),
+                            },
+                            CommandOptionChoiceString {
+                                name: "Done".to_string(),
+                                value: "done".to_string(),
+                            },
+                            CommandOptionChoiceString {
+                                name: "Bio".to_string(),
+                                value: "bio".to_string(),
+                            },
+                            CommandOptionChoiceString {
+                                name: "Info".to_string(),
+                                value: "info".to_string(),
+                            },
+                            CommandOptionChoiceString {
+                                name: "Net Worth".to_string(),
+                                value: "networth".to_string(),
+                            },
+                            CommandOptionChoiceString {
+                                name: "Schedule".to_string(),
+                                value: "schedule".to_string(),
+                            },
+                            CommandOptionChoiceString {
+                                name: "Icebreaker".to_string(),
+                                value: "icebreaker".to_string(),
+                            },
+                            CommandOptionChoiceString {
+                                name: "Culture Quiz".to_string(),
+                                value: "quiz".to_string(),
+                            },
                         ],
                         multi_line: false,
                     }),
                 },
+                BotCommandParam {
+                    name: "args".to_string(),
+                    description: Some("Arguments for the subcommand".to_string()),
+                    placeholder: Some("e.g. learn Spanish beginner".to_string()),
+                    required: false,
+                    param_type: BotCommandParamType::StringParam(StringParam {
+                        min_length: 0,
+                        max_length: 500,
+                        choices: Vec::new(),
+                        multi_line: false,
+                    }),
+                },
             ],
             permissions: Default::default(),
             default_role: None,
@@ -113,43 +190,32 @@ impl PingPairCommand {
     }
 }
 
-// Test function to simulate ping time
-pub fn simulate_ping_time() -> String {
-    // Get available countries
-    let spotlights = vec![
-        ("Kenya", "🇰🇪", vec!["Home to over 40 ethnic groups", "Birthplace of marathon champions", "Famous for wildlife safaris"]),
-        ("India", "🇮🇳", vec!["World's largest democracy", "Home to Bollywood", "Known for diverse cuisine and spices"]),
-        ("Brazil", "🇧🇷", vec!["Famous for Carnival and samba", "Home to most of the Amazon rainforest", "Soccer is a national passion"]),
-    ];
-    
-    // Select a random country
-    let spotlight_index = (std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as usize) % spotlights.len();
-    let (country, emoji, facts) = &spotlights[spotlight_index];
-    
-    // Format facts as bullet points
-    let facts_formatted = facts.iter()
-        .map(|fact| format!("- {}", fact))
-        .collect::<Vec<String>>()
-        .join("\n");
-    
-    format!(r#"
-# It's Ping Time! 🌍
-
-### Global Spotlight: {} {}
-
-**Fun Facts:**
-{}
+// The community a command was sent from, which selects its template
+// overrides and spotlight rotation. Group and direct chats share the
+// global settings.
+fn community_of(ctx: &BotCommandContext) -> String {
+    match &ctx.scope {
+        BotCommandScope::Community(details) => details.community_id.to_string(),
+        BotCommandScope::Chat(details) => match &details.chat {
+            Chat::Channel(community_id, _) => community_id.to_string(),
+            _ => DEFAULT_COMMUNITY.to_string(),
+        },
+    }
+}
 
-Would you love to meet someone from {} or someone interested in exploring {}?
+// The chat or community a command was sent from, for rate limiting. Unlike
+// `community_of`, group and direct chats each get their own key.
+fn chat_of(ctx: &BotCommandContext) -> String {
+    match &ctx.scope {
+        BotCommandScope::Community(details) => details.community_id.to_string(),
+        BotCommandScope::Chat(details) => match &details.chat {
+            Chat::Channel(community_id, _) => community_id.to_string(),
+            Chat::Group(chat_id) | Chat::Direct(chat_id) => chat_id.to_string(),
+        },
+    }
+}
 
-Reply with `yes` to be matched with someone for a cultural exchange!
-    "#, 
-    country, 
-    emoji,
-    facts_formatted,
-    country,
-    country)
-} 
\ No newline at end of file
+// Test function to simulate ping time
+pub fn simulate_ping_time() -> String {
+    api::handlers::handle_ping_command(DEFAULT_COMMUNITY, DEFAULT_LOCALE)
+}
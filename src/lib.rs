@@ -1,4 +1,8 @@
-pub mod api;
+pub use pingpair_core::{api, model};
 pub mod commands;
-pub mod model;
-pub mod config; 
\ No newline at end of file
+pub mod config;
+pub mod logging;
+pub mod oc_keys;
+pub mod server;
+pub mod shutdown;
+pub mod state_file;
@@ -1,37 +1,22 @@
-use axum::{
-    body::Bytes,
-    extract::State,
-    http::{HeaderMap, StatusCode},
-    routing::{get, post},
-    Router,
-};
-use dotenv::dotenv;
-use oc_bots_sdk::api::command::{CommandHandlerRegistry, CommandResponse, CommandHandler};
-use oc_bots_sdk::api::definition::BotDefinition;
-use oc_bots_sdk::oc_api::client::ClientFactory;
-use oc_bots_sdk_offchain::{env, AgentRuntime};
+use oc_bots_sdk::api::command::CommandHandler;
+use oc_bots_sdk_offchain::AgentRuntime;
 use std::net::{Ipv4Addr, SocketAddr};
 use std::sync::Arc;
-use tower_http::cors::CorsLayer;
-use tower_http::trace::TraceLayer;
-use tracing::{info, error};
-use tracing_subscriber::fmt::format::FmtSpan;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{error, info, warn};
 
-mod config;
-mod commands;
-mod model;
-mod api;
-
-// Structure to hold application state
-struct AppState {
-    oc_public_key: String,
-    commands: CommandHandlerRegistry<AgentRuntime>,
-}
+use PingPair::model::clock::SystemClock;
+use PingPair::model::rng::SeededRng;
+use PingPair::server::{self, AppState};
+use PingPair::{commands, config, logging, model, oc_keys, shutdown, state_file};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Load .env file if present
-    dotenv().ok();
+    // Load .env file if present. Key reloads read it again on top of the
+    // environment as it was before
+    let process_env: Vec<(String, String)> = std::env::vars().collect();
+    dotenvy::dotenv().ok();
 
     // Check for test command line arguments
     let args: Vec<String> = std::env::args().collect();
@@ -53,175 +38,123 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    // Get config file path from env - if not set, use default
-    let config_file_path = std::env::var("CONFIG_FILE").unwrap_or("./config.toml".to_string());
-    println!("Config file path: {:?}", config_file_path);
-
-    // Load & parse config
-    let config = config::Config::from_file(&config_file_path)?;
-    println!("Config: {:?}", config);
+    // Load config from the file, PINGPAIR_* env vars and flags
+    let command_line = config::CommandLine::parse(args.into_iter().skip(1))?;
+    let config = config::Config::load(&command_line)?;
+    if command_line.print_config {
+        print!("{}", config.to_redacted_toml());
+    }
+    if let Err(e) = config.validate() {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    }
+    if command_line.print_config {
+        return Ok(());
+    }
+    let (config_file_path, _) = command_line.config_path();
 
     // Setup logging
-    tracing_subscriber::fmt()
-        .with_max_level(config.log_level)
-        .with_span_events(FmtSpan::CLOSE)
-        .init();
-
-    info!("Starting PingPair bot proxy");
-
-    // Initialize app state
-    model::state::initialize_state();
-    
-    // Initialize spotlight countries
-    model::state::initialize_spotlight_countries();
-
-    // Build agent for OpenChat communication
-    let agent = oc_bots_sdk_offchain::build_agent(config.ic_url.clone(), &config.pem_file).await;
-
-    // Create runtime and client factory
-    let runtime = AgentRuntime::new(agent, tokio::runtime::Runtime::new()?);
-    let client_factory = Arc::new(ClientFactory::new(runtime));
+    logging::init(&config)?;
 
-    // Create command registry and register PingPair commands
-    let commands = CommandHandlerRegistry::new(client_factory)
-        .register(commands::pingpair::PingPairCommand);
+    info!(config_file = %config_file_path, config_fingerprint = %config.fingerprint(), "Starting PingPair bot proxy");
+    for variable in config::unknown_env_vars(std::env::vars()) {
+        warn!(variable, "Ignoring environment variable that is not a PingPair setting");
+    }
 
-    let app_state = AppState {
-        oc_public_key: config.oc_public_key,
-        commands,
-    };
+    // Load the saved store before serving, so a bad save stops startup
+    // rather than being overwritten by an empty store on shutdown
+    model::state::initialize_state(Arc::new(SystemClock), Box::new(SeededRng::from_entropy()));
+    if let Some(path) = &config.state_file {
+        state_file::load(path)?;
+    }
 
-    // Create router with endpoints
-    let app = Router::new()
-        .route("/", get(bot_definition))
-        .route("/bot_definition", get(bot_definition))
-        .route("/execute", post(execute_command))
-        .route("/execute_command", post(execute_command))
-        .layer(CorsLayer::permissive())
-        .layer(TraceLayer::new_for_http())
-        .with_state(Arc::new(app_state));
+    let keys = oc_keys::KeyRing::load(&config)?;
+    info!(keys = ?keys.ids(), "OpenChat public keys loaded");
+    let app_state = Arc::new(AppState::new(keys, config.fingerprint()));
+    let app = server::router(app_state.clone());
 
-    // Start HTTP server
+    // Serve probes while the rest of startup runs, so /readyz can say which
+    // step is missing or failed
     let socket_addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), config.port);
     info!("Starting HTTP server on {}", socket_addr);
     
     let listener = tokio::net::TcpListener::bind(socket_addr).await?;
-    axum::serve(listener, app.into_make_service()).await?;
-
-    Ok(())
-}
+    let (stop, stopping) = watch::channel(false);
+    let mut server = tokio::spawn(shutdown::serve(listener, app, stopping.clone()));
 
-// Bot definition endpoint
-async fn bot_definition(State(state): State<Arc<AppState>>) -> (StatusCode, HeaderMap, Bytes) {
-    let commands = state.commands.definitions();
-    
-    // Ensure each command has permissions set correctly
-    let commands_with_permissions = commands.into_iter()
-        .map(|mut cmd| {
-            if cmd.permissions.is_none() {
-                cmd.permissions = Some(serde_json::json!({
-                    "community": 0,
-                    "chat": 0,
-                    "message": 0
-                }));
-            }
-            cmd
-        })
-        .collect();
-    
-    let definition = BotDefinition {
-        description: "Connect people globally through themed cultural exchange meetups".to_string(),
-        commands: commands_with_permissions,
-        autonomous_config: None,
-    };
-    
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        axum::http::header::CONTENT_TYPE,
-        "application/json".parse().unwrap(),
-    );
+    match server::configure(&config) {
+        Ok(()) => {
+            app_state.readiness.set("config", Ok(()));
+            start_commands(&config, &app_state).await?;
+        }
+        Err(e) => {
+            // Keep serving so the failure shows on /readyz
+            error!("Invalid configuration: {}", e);
+            app_state.readiness.set("config", Err(e.to_string()));
+        }
+    }
 
-    (
-        StatusCode::OK,
-        headers,
-        Bytes::from(serde_json::to_vec(&definition).unwrap()),
-    )
-}
+    let flusher = config.state_file.clone().map(|path| {
+        state_file::spawn_flusher(path, Duration::from_secs(config.state_flush_secs), stopping.clone())
+    });
+    oc_keys::spawn_reloader(app_state.oc_keys.clone(), command_line, process_env, stopping.clone());
 
-// Command execution endpoint
-async fn execute_command(
-    State(state): State<Arc<AppState>>, 
-    headers: HeaderMap,
-) -> (StatusCode, Bytes) {
-    info!("=== Command Execution Start ===");
-    info!("Headers: {:?}", headers);
-    
-    // Get JWT from x-oc-jwt header
-    let jwt = match headers.get("x-oc-jwt") {
-        Some(jwt_header) => {
-            match jwt_header.to_str() {
-                Ok(jwt) => {
-                    info!("Found JWT in x-oc-jwt header");
-                    jwt.to_string()
-                },
-                Err(e) => {
-                    error!("Invalid JWT header value: {}", e);
-                    return (
-                        StatusCode::BAD_REQUEST,
-                        Bytes::from("Invalid JWT header value"),
-                    );
-                }
-            }
-        },
-        None => {
-            error!("No JWT found in x-oc-jwt header");
-            return (
-                StatusCode::BAD_REQUEST,
-                Bytes::from("Missing JWT header"),
-            );
+    tokio::select! {
+        _ = shutdown::signal() => {}
+        result = &mut server => {
+            result??;
+            return Ok(());
         }
-    };
+    }
 
-    info!("JWT length: {}", jwt.len());
-    
-    // Parse command data from the JWT payload
-    let result = state
-        .commands
-        .execute(&jwt, &state.oc_public_key, env::now())
-        .await;
-        
-    info!("Command execution result: {:?}", result);
-    info!("=== Command Execution End ===");
-    
-    match result {
-        CommandResponse::Success(r) => {
-            info!("Command executed successfully");
-            (StatusCode::OK, Bytes::from(serde_json::to_vec(&r).unwrap()))
-        }
-        CommandResponse::BadRequest(r) => {
-            error!("Bad request: {:?}", r);
-            (
-                StatusCode::BAD_REQUEST,
-                Bytes::from(serde_json::to_vec(&r).unwrap()),
-            )
+    // Stop taking connections and give in-flight commands time to finish
+    info!("Shutting down: draining in-flight requests for up to {}s", config.shutdown_timeout_secs);
+    let _ = stop.send(true);
+    match tokio::time::timeout(Duration::from_secs(config.shutdown_timeout_secs), &mut server).await {
+        Ok(result) => {
+            result??;
+            info!("In-flight requests drained");
         }
-        CommandResponse::InternalError(err) => {
-            error!("Internal error: {:?}", err);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Bytes::from(format!("{err:?}")),
-            )
-        }
-        CommandResponse::TooManyRequests => {
-            error!("Too many requests");
-            (StatusCode::TOO_MANY_REQUESTS, Bytes::new())
+        Err(_) => {
+            server.abort();
+            warn!("Requests still running after {}s were cut off", config.shutdown_timeout_secs);
         }
     }
+
+    if let Some(flusher) = flusher {
+        flusher.await?;
+    }
+    if let Some(path) = &config.state_file {
+        state_file::save(path)?;
+    }
+
+    // The SDK's runtime cannot be dropped from async code
+    tokio::task::spawn_blocking(move || drop(app_state)).await?;
+    info!("Shutdown complete");
+
+    Ok(())
+}
+
+// Connect to the IC and start taking commands
+async fn start_commands(config: &config::Config, app_state: &AppState) -> Result<(), Box<dyn std::error::Error>> {
+    // Initialize spotlight countries
+    model::state::initialize_spotlight_countries();
+
+    // Build agent for OpenChat communication
+    let agent = oc_bots_sdk_offchain::build_agent(config.ic_url.clone(), &config.pem_file).await;
+
+    // Create the runtime and register the PingPair commands
+    let runtime = AgentRuntime::new(agent, tokio::runtime::Runtime::new()?);
+    let _ = app_state.commands.set(server::command_registry(runtime));
+    app_state.readiness.set("ic_agent", Ok(()));
+    info!("Ready for commands");
+
+    Ok(())
 }
 
 // Simple function to test the ping command
 fn test_ping() {
-    use crate::commands::pingpair::simulate_ping_time;
+    use commands::pingpair::simulate_ping_time;
     
     println!("Running ping test...");
     
@@ -242,12 +175,14 @@ fn test_ping() {
 
 // Simple function to test the help menu
 fn test_help() {
-    use crate::api::handlers::get_help_menu;
+    use PingPair::api::handlers::get_help_menu;
+    use PingPair::model::i18n::DEFAULT_LOCALE;
+    use PingPair::model::spotlight::DEFAULT_COMMUNITY;
     
     println!("Running help menu test...");
     
     // Call the help menu function
-    let result = get_help_menu();
+    let result = get_help_menu(DEFAULT_COMMUNITY, DEFAULT_LOCALE);
     
     // Print the result
     println!("\nHelp menu output:\n{}", result);
@@ -265,7 +200,7 @@ async fn test_server() -> Result<(), Box<dyn std::error::Error>> {
     println!("Running server test...");
     
     // Initialize app state
-    model::state::initialize_state();
+    model::state::initialize_state(Arc::new(SystemClock), Box::new(SeededRng::from_entropy()));
     
     // Get command definition directly from the PingPairCommand impl
     let cmd = commands::pingpair::PingPairCommand;
@@ -0,0 +1,305 @@
+use std::collections::HashSet;
+use std::fs;
+use std::sync::{Arc, RwLock};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+use crate::config::{parse_public_key, CommandLine, Config, OcPublicKey};
+use pingpair_core::api::calendar;
+
+// Id of the key set with `oc_public_key`, which has no validity window
+pub const PRIMARY: &str = "primary";
+
+// Reported in place of a key id when no key accepted the token, so no
+// configured key may use it
+pub const NO_KEY: &str = "none";
+
+// An OpenChat public key that command JWTs may be signed with
+#[derive(Debug, Clone, PartialEq)]
+pub struct OcKey {
+    pub id: String,
+    pub pem: String,
+    // Unix seconds
+    not_before: Option<u64>,
+    not_after: Option<u64>,
+}
+
+impl OcKey {
+    fn load(key: &OcPublicKey) -> Result<Self, String> {
+        let pem = match (&key.pem, &key.pem_file) {
+            (Some(pem), None) => pem.clone(),
+            (None, Some(path)) => fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?,
+            _ => return Err("set exactly one of pem and pem_file".to_string()),
+        };
+        parse_public_key(&pem)?;
+
+        let not_before = window_time("not_before", &key.not_before)?;
+        let not_after = window_time("not_after", &key.not_after)?;
+        if let (Some(not_before), Some(not_after)) = (not_before, not_after) {
+            if not_before >= not_after {
+                return Err("not_before must be earlier than not_after".to_string());
+            }
+        }
+
+        Ok(OcKey { id: key.id.clone(), pem, not_before, not_after })
+    }
+
+    pub fn valid_at(&self, now_secs: u64) -> bool {
+        self.not_before.is_none_or(|not_before| now_secs >= not_before)
+            && self.not_after.is_none_or(|not_after| now_secs < not_after)
+    }
+}
+
+fn window_time(name: &str, time: &Option<String>) -> Result<Option<u64>, String> {
+    time.as_deref()
+        .map(|time| {
+            calendar::parse_utc(time).ok_or_else(|| format!("{} {:?} is not a UTC time like 2026-11-01T00:00Z", name, time))
+        })
+        .transpose()
+}
+
+// Every OpenChat public key from the config, in the order they are tried
+#[derive(Debug, Default)]
+pub struct KeyRing {
+    keys: Vec<OcKey>,
+}
+
+impl KeyRing {
+    // Reads the key files; reports every bad key at once, one per line
+    pub fn load(config: &Config) -> Result<Self, String> {
+        let mut keys = Vec::new();
+        let mut errors = Vec::new();
+
+        if !config.oc_public_key.trim().is_empty() {
+            match parse_public_key(&config.oc_public_key) {
+                Ok(_) => keys.push(OcKey {
+                    id: PRIMARY.to_string(),
+                    pem: config.oc_public_key.clone(),
+                    not_before: None,
+                    not_after: None,
+                }),
+                Err(e) => errors.push(format!("oc_public_key: {}", e)),
+            }
+        }
+
+        let mut ids = HashSet::from([PRIMARY]);
+        for (i, key) in config.oc_public_keys.iter().enumerate() {
+            let name = format!("oc_public_keys[{}]", i);
+            if key.id.trim().is_empty() {
+                errors.push(format!("{}: needs an id", name));
+            } else if key.id == NO_KEY {
+                errors.push(format!("{}: id {:?} is reserved for tokens no key accepts", name, NO_KEY));
+            } else if !ids.insert(key.id.as_str()) {
+                errors.push(format!("{}: id {:?} is already used", name, key.id));
+            }
+            match OcKey::load(key) {
+                Ok(key) => keys.push(key),
+                Err(e) => errors.push(format!("{} ({}): {}", name, key.id, e)),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(KeyRing { keys })
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
+    // The keys to try for a token received at `now_secs`
+    pub fn valid_at(&self, now_secs: u64) -> impl Iterator<Item = &OcKey> {
+        self.keys.iter().filter(move |key| key.valid_at(now_secs))
+    }
+
+    pub fn ids(&self) -> Vec<&str> {
+        self.keys.iter().map(|key| key.id.as_str()).collect()
+    }
+}
+
+// The key ring in use, swapped whole on reload so that a request sees
+// either the old keys or the new ones
+pub struct OcKeys {
+    ring: RwLock<Arc<KeyRing>>,
+}
+
+impl OcKeys {
+    pub fn new(ring: KeyRing) -> Self {
+        OcKeys { ring: RwLock::new(Arc::new(ring)) }
+    }
+
+    pub fn current(&self) -> Arc<KeyRing> {
+        self.ring.read().unwrap().clone()
+    }
+
+    // Read the keys again from every config layer, with `env` as the
+    // environment (see `reload_env`). On error the current keys stay in use.
+    pub fn reload(&self, command_line: &CommandLine, env: Vec<(String, String)>) -> Result<Arc<KeyRing>, String> {
+        let config = Config::load_with_env(command_line, env)?;
+        let ring = Arc::new(KeyRing::load(&config)?);
+        if ring.keys.is_empty() {
+            return Err("No OpenChat public key configured".to_string());
+        }
+
+        *self.ring.write().unwrap() = ring.clone();
+        Ok(ring)
+    }
+}
+
+// The environment for a reload: `process_env`, the variables the process
+// started with before .env was applied, then .env read again. The process
+// environment cannot change after start, so this is how an edited .env takes
+// effect; as at startup, a variable set in both keeps its process value.
+pub fn reload_env(process_env: &[(String, String)]) -> Vec<(String, String)> {
+    let mut env = process_env.to_vec();
+    // Reads .env without setting variables
+    if let Ok(dotenv) = dotenvy::dotenv_iter() {
+        env.extend(dotenv.flatten());
+    }
+    env
+}
+
+// Reload the keys on SIGHUP until `stopping` turns true
+pub fn spawn_reloader(
+    keys: Arc<OcKeys>,
+    command_line: CommandLine,
+    process_env: Vec<(String, String)>,
+    mut stopping: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        #[cfg(unix)]
+        let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(e) => {
+                error!("Cannot listen for SIGHUP: {}", e);
+                return;
+            }
+        };
+
+        loop {
+            #[cfg(unix)]
+            let reload = hangup.recv();
+            #[cfg(not(unix))]
+            let reload = std::future::pending::<Option<()>>();
+
+            tokio::select! {
+                _ = stopping.wait_for(|stop| *stop) => break,
+                _ = reload => match keys.reload(&command_line, reload_env(&process_env)) {
+                    Ok(ring) => info!(keys = ?ring.ids(), "OpenChat public keys reloaded"),
+                    Err(e) => error!("Keeping the current OpenChat public keys: {}", e),
+                },
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUBLIC_KEY: &str = "-----BEGIN PUBLIC KEY-----
+MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEquEHzJr9605Oy796e4z7LKow46DV
+NUnDOQWavi86vEhRAAfdbVh/Lgmxfi44LPb6S0wnCRm9kI/XdK1DYw2Eaw==
+-----END PUBLIC KEY-----";
+
+    // 2026-11-01T00:00:00Z
+    const NOVEMBER: u64 = 1_793_491_200;
+
+    fn key(id: &str, not_before: Option<&str>, not_after: Option<&str>) -> OcPublicKey {
+        OcPublicKey {
+            id: id.to_string(),
+            pem: Some(PUBLIC_KEY.to_string()),
+            pem_file: None,
+            not_before: not_before.map(String::from),
+            not_after: not_after.map(String::from),
+        }
+    }
+
+    #[test]
+    fn keys_are_tried_within_their_windows() {
+        let config = Config {
+            oc_public_key: PUBLIC_KEY.to_string(),
+            oc_public_keys: vec![
+                key("2026-10", None, Some("2026-11-01T00:00Z")),
+                key("2026-11", Some("2026-11-01T00:00Z"), None),
+            ],
+            ..Config::default()
+        };
+        let ring = KeyRing::load(&config).unwrap();
+
+        let ids = |now| ring.valid_at(now).map(|key| key.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ring.ids(), vec![PRIMARY, "2026-10", "2026-11"]);
+        assert_eq!(ids(NOVEMBER - 1), vec![PRIMARY, "2026-10"]);
+        assert_eq!(ids(NOVEMBER), vec![PRIMARY, "2026-11"]);
+    }
+
+    #[test]
+    fn bad_keys_are_reported_together() {
+        let config = Config {
+            oc_public_key: "not a pem".to_string(),
+            oc_public_keys: vec![
+                key(PRIMARY, None, None),
+                key(NO_KEY, None, None),
+                key("late", Some("2026-12-01T00:00Z"), Some("2026-11-01T00:00Z")),
+                key("garbled", Some("next tuesday"), None),
+                OcPublicKey { id: "missing".to_string(), pem_file: Some("/nonexistent/oc.pem".to_string()), ..OcPublicKey::default() },
+                OcPublicKey { pem_file: Some("oc.pem".to_string()), ..key("both", None, None) },
+            ],
+            ..Config::default()
+        };
+
+        let error = KeyRing::load(&config).unwrap_err();
+        assert!(error.contains("oc_public_key: not a PEM public key"), "{}", error);
+        assert!(error.contains("oc_public_keys[0]: id \"primary\" is already used"), "{}", error);
+        assert!(error.contains("oc_public_keys[1]: id \"none\" is reserved"), "{}", error);
+        assert!(error.contains("oc_public_keys[2] (late): not_before must be earlier"), "{}", error);
+        assert!(error.contains("oc_public_keys[3] (garbled): not_before \"next tuesday\""), "{}", error);
+        assert!(error.contains("oc_public_keys[4] (missing): cannot read /nonexistent/oc.pem"), "{}", error);
+        assert!(error.contains("oc_public_keys[5] (both): set exactly one of pem and pem_file"), "{}", error);
+        assert_eq!(error.lines().count(), 7);
+    }
+
+    #[test]
+    fn reload_swaps_in_keys_from_disk_and_keeps_them_on_error() {
+        let dir = std::env::temp_dir().join(format!("pingpair-oc-keys-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let config_file = dir.join("config.toml");
+        let key_file = dir.join("next.pem");
+        fs::write(&key_file, PUBLIC_KEY).unwrap();
+        let command_line = CommandLine {
+            config_file: Some(config_file.display().to_string()),
+            ..CommandLine::default()
+        };
+        let keys = OcKeys::new(KeyRing::default());
+
+        fs::write(&config_file, format!("[[oc_public_keys]]\nid = \"next\"\npem_file = \"{}\"\n", key_file.display())).unwrap();
+        let before = keys.current();
+        assert_eq!(keys.reload(&command_line, Vec::new()).unwrap().ids(), vec!["next"]);
+        assert!(before.ids().is_empty());
+
+        fs::write(&key_file, "-----BEGIN PUBLIC KEY-----\n!!\n-----END PUBLIC KEY-----").unwrap();
+        assert!(keys.reload(&command_line, Vec::new()).unwrap_err().contains("(next): PEM body is not valid base64"));
+        fs::write(&config_file, "").unwrap();
+        assert!(keys.reload(&command_line, Vec::new()).is_err());
+        assert_eq!(keys.current().ids(), vec!["next"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn the_process_environment_wins_over_dotenv_on_reload() {
+        const DOTENV_KEY: &str = "-----BEGIN PUBLIC KEY-----
+MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEO2KjGekUmHMNdY/n16xzFtfqivwo
+Wdck4HyhRF1ebS2QncxUHIjiy60uGeygnyRwPXgbdRxY5h2W87ayjW7dkg==
+-----END PUBLIC KEY-----";
+        let keys = OcKeys::new(KeyRing::default());
+        let process = ("PINGPAIR_OC_PUBLIC_KEY".to_string(), PUBLIC_KEY.to_string());
+        let dotenv = ("PINGPAIR_OC_PUBLIC_KEY".to_string(), DOTENV_KEY.to_string());
+
+        // `reload_env` lists the process environment first, and the first
+        // value wins whichever sorts first
+        let ring = keys.reload(&CommandLine::default(), vec![process.clone(), dotenv.clone()]).unwrap();
+        assert_eq!(ring.keys[0].pem, PUBLIC_KEY);
+        let ring = keys.reload(&CommandLine::default(), vec![dotenv, process]).unwrap();
+        assert_eq!(ring.keys[0].pem, DOTENV_KEY);
+    }
+}
@@ -1,4 +1,5 @@
-use candid::{CandidType, Deserialize};
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::management_canister::main::raw_rand;
 use ic_cdk::api::time;
 use ic_cdk_macros::*;
 use openchat_rust_sdk::{
@@ -19,257 +20,134 @@ use openchat_rust_sdk::{
         UserId,
     },
 };
+use pingpair_core::api::{handlers, queries};
+use pingpair_core::model::clock::Clock;
+use pingpair_core::model::i18n::{t, DEFAULT_LOCALE};
+use pingpair_core::model::rng::SeededRng;
+use pingpair_core::model::snapshot;
+use pingpair_core::model::spotlight::DEFAULT_COMMUNITY;
+use pingpair_core::model::state;
+use pingpair_core::model::types::{BotStats, LeaderboardEntry, Pairing, Session, UserProfile};
+use rand_chacha::rand_core::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use serde_json::json;
-use std::collections::HashMap;
 use std::cell::RefCell;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::Duration;
 
-type UserStore = HashMap<String, User>;
-type MatchStore = HashMap<String, MatchInfo>;
+// The canister is a thin adapter: users, matching, Strix and replies all
+// come from pingpair-core, exactly as for the off-chain bot.
 
-thread_local! {
-    static USERS: RefCell<UserStore> = RefCell::new(HashMap::new());
-    static MATCHES: RefCell<MatchStore> = RefCell::new(HashMap::new());
-}
+struct IcClock;
 
-#[derive(Clone, Debug)]
-struct User {
-    user_id: String,
-    strix_points: u32,
-    is_active: bool,
-    timezone: String,
-    interests: Vec<String>,
-    match_history: Vec<String>,
-    skip_next_match: bool,
+impl Clock for IcClock {
+    fn now(&self) -> u64 {
+        time()
+    }
 }
 
-#[derive(Clone, Debug)]
-struct MatchInfo {
-    match_id: String,
-    user1_id: String,
-    user2_id: String,
-    spotlight_country: String,
-    meeting_link: String,
-    created_at: u64,
-    is_completed: bool,
+thread_local! {
+    // Entropy for meeting rooms and calendar secrets, seeded from the
+    // management canister's raw_rand once the canister is running
+    static CSPRNG: RefCell<Option<ChaCha20Rng>> = const { RefCell::new(None) };
 }
 
-// Countries database for spotlights
-fn get_country_info(country_name: &str) -> Option<CountryInfo> {
-    match country_name {
-        "Japan" => Some(CountryInfo {
-            name: "Japan".to_string(),
-            flag: "🇯🇵".to_string(),
-            facts: vec![
-                "Island nation in East Asia".to_string(),
-                "Population of about 125 million".to_string(),
-                "Known for technological innovation and ancient traditions".to_string(),
-            ],
-            traditions: Some(vec![
-                "Tea ceremonies (Chado)".to_string(),
-                "Cherry blossom (Sakura) viewing".to_string(),
-                "Traditional arts like origami and calligraphy".to_string(),
-            ]),
-        }),
-        "Kenya" => Some(CountryInfo {
-            name: "Kenya".to_string(),
-            flag: "🇰🇪".to_string(),
-            facts: vec![
-                "East African nation known for wildlife and scenery".to_string(),
-                "Population of about 54 million".to_string(),
-                "Home to the Great Rift Valley and Lake Victoria".to_string(),
-            ],
-            traditions: Some(vec![
-                "Safari tourism".to_string(),
-                "Maasai cultural traditions".to_string(),
-                "Kenyan long-distance running excellence".to_string(),
-            ]),
-        }),
-        "Brazil" => Some(CountryInfo {
-            name: "Brazil".to_string(),
-            flag: "🇧🇷".to_string(),
-            facts: vec![
-                "Largest country in South America".to_string(),
-                "Population of about 213 million".to_string(),
-                "Home to a significant portion of the Amazon rainforest".to_string(),
-            ],
-            traditions: Some(vec![
-                "Carnival celebrations".to_string(),
-                "Samba music and dance".to_string(),
-                "Soccer (football) culture".to_string(),
-            ]),
-        }),
-        _ => None,
-    }
+// getrandom error code while the CSPRNG is waiting for its seed
+const NOT_SEEDED: u32 = getrandom::Error::CUSTOM_START;
+
+// getrandom has no entropy source in a canister; serve it from the CSPRNG
+// and fail until that has been seeded, rather than hand out guessable bytes
+fn canister_random(buf: &mut [u8]) -> Result<(), getrandom::Error> {
+    CSPRNG.with(|csprng| match csprng.borrow_mut().as_mut() {
+        Some(csprng) => {
+            csprng.fill_bytes(buf);
+            Ok(())
+        }
+        None => Err(getrandom::Error::from(NonZeroU32::new(NOT_SEEDED).unwrap())),
+    })
 }
 
-struct CountryInfo {
-    name: String,
-    flag: String,
-    facts: Vec<String>,
-    traditions: Option<Vec<String>>,
+getrandom::register_custom_getrandom!(canister_random);
+
+// raw_rand is an inter-canister call, which init and post_upgrade cannot
+// make, so it runs from a timer right after them
+fn schedule_seeding(delay: Duration) {
+    ic_cdk_timers::set_timer(delay, || ic_cdk::spawn(seed_csprng()));
 }
 
-// Command handler function
-fn handle_pingpair_command(command: Command) -> BotResponse {
-    let user_id = command.initiator.to_string();
-    let args = command.args;
-    
-    let subcommand = if args.is_empty() {
-        "help"
-    } else {
-        &args[0]
+async fn seed_csprng() {
+    let seed = match raw_rand().await {
+        Ok((bytes,)) => <[u8; 32]>::try_from(bytes).map_err(|bytes| format!("raw_rand returned {} bytes", bytes.len())),
+        Err((code, message)) => Err(format!("raw_rand failed: {:?} {}", code, message)),
     };
-    
-    match subcommand {
-        "start" => handle_start(user_id),
-        "profile" => handle_profile(user_id, args.get(1..).unwrap_or(&[])),
-        "skip" => handle_skip(user_id),
-        "stats" => handle_stats(user_id),
-        "timezone" => handle_timezone(user_id, args.get(1..).unwrap_or(&[])),
-        _ => handle_help(),
+
+    match seed {
+        Ok(seed) => {
+            let mut csprng = ChaCha20Rng::from_seed(seed);
+            // Shuffles get their own stream, so their output says nothing
+            // about meeting rooms or secrets
+            state::set_rng(Box::new(SeededRng::new(csprng.next_u64())));
+            CSPRNG.with(|rng| *rng.borrow_mut() = Some(csprng));
+        }
+        Err(err) => {
+            ic_cdk::println!("Cannot seed the CSPRNG, retrying: {}", err);
+            schedule_seeding(Duration::from_secs(10));
+        }
     }
 }
 
-// Message handler function
-fn handle_message(_message: Message) -> BotResponse {
-    BotResponse::Text("Use /pingpair commands to interact with PingPair bot!".to_string())
+fn setup_runtime() {
+    // Until raw_rand answers, shuffles run from the time; nothing secret
+    // depends on them
+    state::initialize_state(Arc::new(IcClock), Box::new(SeededRng::new(time())));
+    schedule_seeding(Duration::ZERO);
 }
 
-// Command handlers
-fn handle_start(user_id: String) -> BotResponse {
-    USERS.with(|users| {
-        let mut users_map = users.borrow_mut();
-        
-        if !users_map.contains_key(&user_id) {
-            users_map.insert(user_id.clone(), User {
-                user_id: user_id.clone(),
-                strix_points: 5,
-                is_active: true,
-                timezone: "UTC".to_string(),
-                interests: Vec::new(),
-                match_history: Vec::new(),
-                skip_next_match: false,
-            });
-            
-            return BotResponse::Text(
-                "Welcome to PingPair! 🌍✨\n\nI'll connect you with someone from a different part of the world twice a week for cultural exchange meetups.\n\nUse /pingpair profile to set up your profile and start getting matched!".to_string()
-            );
-        }
-        
-        let mut user = users_map.get_mut(&user_id).unwrap();
-        user.is_active = true;
-        
-        BotResponse::Text(
-            "Welcome back to PingPair! You're now active and will receive match notifications. Use /pingpair profile to update your profile.".to_string()
-        )
-    })
+fn initialize() {
+    state::initialize_spotlight_countries();
 }
 
-fn handle_profile(user_id: String, args: &[String]) -> BotResponse {
-    USERS.with(|users| {
-        let mut users_map = users.borrow_mut();
-        
-        if !users_map.contains_key(&user_id) {
-            return handle_start(user_id);
-        }
-        
-        let user = users_map.get_mut(&user_id).unwrap();
-        
-        // If no arguments, show current profile
-        if args.is_empty() {
-            return BotResponse::Text(
-                format!(
-                    "🌟 **Your PingPair Profile**\n\nTimezone: {}\nInterests: {}\nStrix Points: {}\nMatches: {}\n\nUse /pingpair profile add [interest] to add interests",
-                    user.timezone,
-                    user.interests.join(", "),
-                    user.strix_points,
-                    user.match_history.len()
-                )
-            );
-        }
-        
-        // Handle profile subcommands
-        let subcommand = &args[0];
-        
-        if subcommand == "add" && args.len() > 1 {
-            let interest = args[1..].join(" ");
-            if !user.interests.contains(&interest) {
-                user.interests.push(interest.clone());
-                user.strix_points += 1;
-            }
-            
-            return BotResponse::Text(
-                format!("Added \"{}\" to your interests! You now have {} Strix Points.", interest, user.strix_points)
-            );
-        }
-        
-        BotResponse::Text("To update your profile, use:\n/pingpair profile add [interest]".to_string())
-    })
+#[init]
+fn init() {
+    setup_runtime();
+    initialize();
 }
 
-fn handle_skip(user_id: String) -> BotResponse {
-    USERS.with(|users| {
-        let mut users_map = users.borrow_mut();
-        
-        if !users_map.contains_key(&user_id) {
-            return handle_start(user_id);
-        }
-        
-        let user = users_map.get_mut(&user_id).unwrap();
-        user.skip_next_match = true;
-        
-        BotResponse::Text("You'll skip the next match. Use /pingpair start to activate matching again.".to_string())
-    })
+// The heap is wiped on upgrade, so the store is carried across in stable
+// memory as a versioned snapshot
+#[pre_upgrade]
+fn pre_upgrade() {
+    let bytes = snapshot::save().unwrap_or_else(|err| ic_cdk::trap(&err));
+    ic_cdk::storage::stable_save((bytes,)).unwrap_or_else(|err| ic_cdk::trap(&format!("Cannot save state: {}", err)));
 }
 
-fn handle_stats(user_id: String) -> BotResponse {
-    USERS.with(|users| {
-        let users_map = users.borrow();
-        
-        if !users_map.contains_key(&user_id) {
-            return handle_start(user_id);
-        }
-        
-        let user = users_map.get(&user_id).unwrap();
-        
-        BotResponse::Text(
-            format!(
-                "✨ **Your PingPair Stats**\n\nStrix Points: {}\nTotal Matches: {}\nActive: {}\n\nKeep participating to earn more Strix Points!",
-                user.strix_points,
-                user.match_history.len(),
-                if user.is_active { "Yes" } else { "No" }
-            )
-        )
-    })
+#[post_upgrade]
+fn post_upgrade() {
+    setup_runtime();
+
+    // Builds from before these hooks left stable memory empty. Anything else
+    // that fails to load traps, which rolls the upgrade back rather than
+    // start from an empty store that the next upgrade would save over it.
+    if ic_cdk::api::stable::stable_size() > 0 {
+        let (bytes,) = ic_cdk::storage::stable_restore::<(Vec<u8>,)>()
+            .unwrap_or_else(|err| ic_cdk::trap(&format!("Cannot read state from stable memory: {}", err)));
+        snapshot::load(&bytes).unwrap_or_else(|err| ic_cdk::trap(&err));
+    }
+    initialize();
 }
 
-fn handle_timezone(user_id: String, args: &[String]) -> BotResponse {
-    USERS.with(|users| {
-        let mut users_map = users.borrow_mut();
-        
-        if !users_map.contains_key(&user_id) {
-            return handle_start(user_id);
-        }
-        
-        let user = users_map.get_mut(&user_id).unwrap();
-        
-        if args.is_empty() {
-            return BotResponse::Text(
-                format!("Your current timezone is set to: {}\n\nUse /pingpair timezone [your timezone] to update it.", user.timezone)
-            );
-        }
-        
-        let timezone = args.join(" ");
-        user.timezone = timezone.clone();
-        
-        BotResponse::Text(format!("Your timezone has been updated to: {}", timezone))
-    })
+// Command handler function
+fn handle_pingpair_command(command: Command) -> BotResponse {
+    let user_id = command.initiator.to_string();
+    let username = "user".to_string(); // Not part of the command event
+
+    BotResponse::Text(handlers::dispatch(user_id, username, command.args, DEFAULT_COMMUNITY))
 }
 
-fn handle_help() -> BotResponse {
-    BotResponse::Text(
-        "**PingPair Bot Commands**\n\n/pingpair start - Begin receiving match pings\n/pingpair profile - View and update profile\n/pingpair skip - Skip current matching cycle\n/pingpair stats - View Strix points and match history\n/pingpair timezone - Update timezone preference".to_string()
-    )
+// Message handler function
+fn handle_message(_message: Message) -> BotResponse {
+    BotResponse::Text(t(DEFAULT_LOCALE, "general.greeting", &[]))
 }
 
 #[update]
@@ -289,4 +167,78 @@ async fn handle_event(args: EventArgs) -> String {
 #[derive(CandidType, Deserialize)]
 struct EventArgs {
     event: String,
-} 
\ No newline at end of file
+}
+
+// Typed read access for dashboards and other canisters; see pingpair_bot.did
+#[query]
+fn get_profile(user_id: Principal) -> Option<UserProfile> {
+    queries::get_profile(ic_cdk::caller(), user_id)
+}
+
+#[query]
+fn get_leaderboard(limit: u32, offset: u32) -> Vec<LeaderboardEntry> {
+    queries::get_leaderboard(limit, offset)
+}
+
+#[query]
+fn get_current_session() -> Option<Session> {
+    queries::get_current_session(ic_cdk::caller())
+}
+
+#[query]
+fn get_pairing(pairing_id: String) -> Option<Pairing> {
+    queries::get_pairing(ic_cdk::caller(), &pairing_id)
+}
+
+#[query]
+fn get_stats() -> BotStats {
+    queries::get_stats()
+}
+
+ic_cdk::export_candid!();
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::de::IDLDeserialize;
+    use candid::utils::{encode_args, ArgumentDecoder};
+    use candid_parser::utils::{service_equal, CandidSource};
+    use pingpair_core::model::clock::SystemClock;
+    use std::path::Path;
+
+    // Stable memory grows in pages of this size, zero filled
+    const WASM_PAGE: usize = 64 * 1024;
+
+    #[test]
+    fn did_file_matches_the_rust_interface() {
+        let did = Path::new(env!("CARGO_MANIFEST_DIR")).join("pingpair_bot.did");
+        service_equal(CandidSource::Text(&__export_service()), CandidSource::File(&did))
+            .expect("pingpair_bot.did is out of date with the canister methods");
+    }
+
+    #[test]
+    fn state_survives_an_upgrade_through_stable_memory() {
+        state::initialize_state(Arc::new(SystemClock), Box::new(SeededRng::new(1)));
+        let user_id = Principal::from_slice(&[7]).to_text();
+        state::create_user(Principal::from_slice(&[7]), "Amina".to_string());
+
+        // pre_upgrade: what stable_save writes
+        let mut stable = encode_args((snapshot::save().unwrap(),)).unwrap();
+        stable.resize(stable.len().div_ceil(WASM_PAGE) * WASM_PAGE, 0);
+
+        // The upgrade wipes the heap
+        state::restore_state(Default::default()).unwrap();
+        assert!(state::get_user(&user_id).is_none());
+
+        // post_upgrade: what stable_restore reads back, trailing page included
+        let mut de = IDLDeserialize::new(&stable).unwrap();
+        let (bytes,): (Vec<u8>,) = ArgumentDecoder::decode(&mut de).unwrap();
+        snapshot::load(&bytes).unwrap();
+        assert_eq!(state::get_user(&user_id).unwrap().name, "Amina");
+
+        // A snapshot in an unexpected layout is an error, not an empty store
+        let mut de = IDLDeserialize::new(&encode_args(("not a snapshot",)).unwrap()).unwrap();
+        assert!(<(Vec<u8>,)>::decode(&mut de).is_err());
+        assert!(snapshot::load(b"not a snapshot").is_err());
+    }
+}
@@ -0,0 +1,353 @@
+use crate::config::CalendarConfig;
+use crate::model::types::{Country, Pairing, PairingStatus};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::sync::OnceLock;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+// Calls are booked for this long unless the partners say otherwise
+pub const DEFAULT_DURATION_MINS: u64 = 30;
+
+// RFC 5545 §3.1: content lines are folded at 75 octets
+const MAX_LINE_OCTETS: usize = 75;
+
+// Days since 1970-01-01 for a proleptic Gregorian date
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = (if year >= 0 { year } else { year - 399 }) / 400;
+    let yoe = year - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = (if days >= 0 { days } else { days - 146_096 }) / 146_097;
+    let doe = days - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// Unix seconds as an iCalendar UTC date-time, e.g. 20261020T150000Z
+pub fn format_utc(secs: u64) -> String {
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let rem = secs % 86_400;
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        (rem % 3_600) / 60,
+        rem % 60
+    )
+}
+
+// Unix seconds for display to users, e.g. 2026-10-20 15:00
+pub fn format_readable(secs: u64) -> String {
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let rem = secs % 86_400;
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, rem / 3_600, (rem % 3_600) / 60)
+}
+
+// Parse a UTC time typed by a user: `2026-10-20 15:00`, `2026-10-20T15:00`
+// or `2026-10-20T15:00Z`. Returns unix seconds.
+pub fn parse_utc(input: &str) -> Option<u64> {
+    let input = input.trim().trim_end_matches(['Z', 'z']);
+    let (date, time) = input.split_once(['T', 't', ' '])?;
+
+    let mut date_parts = date.split('-');
+    let year: i64 = date_parts.next()?.parse().ok()?;
+    let month: u32 = date_parts.next()?.parse().ok()?;
+    let day: u32 = date_parts.next()?.parse().ok()?;
+
+    let mut time_parts = time.trim().split(':');
+    let hour: u64 = time_parts.next()?.parse().ok()?;
+    let minute: u64 = time_parts.next()?.parse().ok()?;
+    let second: u64 = time_parts.next().map(|s| s.parse().ok()).unwrap_or(Some(0))?;
+
+    if date_parts.next().is_some() || time_parts.next().is_some() {
+        return None;
+    }
+    if !(1..=12).contains(&month) || day == 0 || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    // Reject dates like 2026-02-30 by round-tripping through the calendar
+    let days = days_from_civil(year, month, day);
+    if civil_from_days(days) != (year, month, day) || days < 0 {
+        return None;
+    }
+
+    Some(days as u64 * 86_400 + hour * 3_600 + minute * 60 + second)
+}
+
+// Escape TEXT values (RFC 5545 §3.3.11)
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// Fold a content line into 75-octet chunks without splitting a UTF-8 character
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut octets = 0;
+
+    for c in line.chars() {
+        // Continuation lines start with a space, which counts towards the limit
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+
+    folded.push_str("\r\n");
+    folded
+}
+
+// A VEVENT for a confirmed pairing. The UID is derived from the pairing id so
+// reschedules (with a higher SEQUENCE) update the same calendar entry.
+pub fn pairing_event(pairing: &Pairing, country: Option<&Country>, now_secs: u64) -> Option<Vec<String>> {
+    let start = pairing.meeting_time? / NANOS_PER_SEC;
+    let end = start + DEFAULT_DURATION_MINS * 60;
+
+    let spotlight = match country {
+        Some(country) => format!("{} {}", country.name, country.flag),
+        None => pairing.country.clone(),
+    };
+    let link = pairing.meeting_link.as_ref().map(|link| link.url.as_str());
+
+    let mut description = format!("Your PingPair cultural exchange.\nSpotlight: {}", spotlight);
+    if let Some(country) = country {
+        if let Some(fact) = country.fun_facts.first() {
+            description.push_str(&format!("\nDid you know? {}", fact));
+        }
+    }
+    if let Some(exchange) = &pairing.exchange {
+        description.push_str(&format!(
+            "\nLanguage exchange: {} min {}, then {} min {}",
+            exchange.minutes_per_language, exchange.user1_teaches, exchange.minutes_per_language, exchange.user2_teaches
+        ));
+    }
+    if let Some(link) = link {
+        description.push_str(&format!("\nJoin: {}", link));
+    }
+
+    let status = match pairing.status {
+        PairingStatus::Cancelled => "CANCELLED",
+        _ => "CONFIRMED",
+    };
+
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}@pingpair", pairing.id),
+        format!("DTSTAMP:{}", format_utc(now_secs)),
+        format!("DTSTART:{}", format_utc(start)),
+        format!("DTEND:{}", format_utc(end)),
+        format!("SEQUENCE:{}", pairing.schedule_sequence),
+        format!("STATUS:{}", status),
+        format!("SUMMARY:{}", escape_text(&format!("PingPair: {} cultural exchange", pairing.country))),
+        format!("DESCRIPTION:{}", escape_text(&description)),
+    ];
+    if let Some(link) = link {
+        lines.push(format!("LOCATION:{}", escape_text(link)));
+        lines.push(format!("URL:{}", link));
+    }
+    for trigger in ["-PT1H", "-PT10M"] {
+        lines.extend([
+            "BEGIN:VALARM".to_string(),
+            "ACTION:DISPLAY".to_string(),
+            format!("DESCRIPTION:{}", escape_text("Your PingPair call is coming up")),
+            format!("TRIGGER:{}", trigger),
+            "END:VALARM".to_string(),
+        ]);
+    }
+    lines.push("END:VEVENT".to_string());
+
+    Some(lines)
+}
+
+// A VCALENDAR with one VEVENT per scheduled pairing
+pub fn render_calendar(pairings: &[(Pairing, Option<Country>)], now_secs: u64) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//PingPair//PingPair Bot//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+    ];
+    for (pairing, country) in pairings {
+        if let Some(event) = pairing_event(pairing, country.as_ref(), now_secs) {
+            lines.extend(event);
+        }
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+// Signed, time-limited calendar links
+
+struct Signer {
+    public_url: String,
+    secret: Vec<u8>,
+    link_ttl_secs: u64,
+}
+
+static SIGNER: OnceLock<Signer> = OnceLock::new();
+
+pub fn configure(config: &CalendarConfig) -> Result<(), String> {
+    let secret = match &config.secret {
+        Some(secret) if !secret.is_empty() => secret.as_bytes().to_vec(),
+        _ => {
+            // Links signed with a per-process secret stop working on restart
+            tracing::warn!("No calendar secret configured; calendar links will not survive a restart");
+            let mut secret = vec![0u8; 32];
+            getrandom::getrandom(&mut secret).map_err(|e| e.to_string())?;
+            secret
+        }
+    };
+
+    SIGNER
+        .set(Signer {
+            public_url: config.public_url.trim_end_matches('/').to_string(),
+            secret,
+            link_ttl_secs: config.link_ttl_secs,
+        })
+        .map_err(|_| "Calendar already configured".to_string())
+}
+
+fn signer() -> &'static Signer {
+    SIGNER.get_or_init(|| {
+        let mut secret = vec![0u8; 32];
+        getrandom::getrandom(&mut secret).expect("OS random number generator unavailable");
+        Signer {
+            public_url: CalendarConfig::default().public_url,
+            secret,
+            link_ttl_secs: CalendarConfig::default().link_ttl_secs,
+        }
+    })
+}
+
+fn mac(secret: &[u8], pairing_id: &str, expires: u64) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}:{}", pairing_id, expires).as_bytes());
+    mac
+}
+
+fn sign_with(secret: &[u8], pairing_id: &str, expires: u64) -> String {
+    hex::encode(mac(secret, pairing_id, expires).finalize().into_bytes())
+}
+
+fn verify_with(secret: &[u8], pairing_id: &str, expires: u64, signature: &str, now_secs: u64) -> bool {
+    if expires < now_secs {
+        return false;
+    }
+    match hex::decode(signature) {
+        Ok(signature) => mac(secret, pairing_id, expires).verify_slice(&signature).is_ok(),
+        Err(_) => false,
+    }
+}
+
+// Download link for a pairing's invite, valid for the configured TTL
+pub fn signed_url(pairing_id: &str, now_secs: u64) -> String {
+    let signer = signer();
+    let expires = now_secs + signer.link_ttl_secs;
+    format!(
+        "{}/calendar/{}?expires={}&sig={}",
+        signer.public_url,
+        pairing_id,
+        expires,
+        sign_with(&signer.secret, pairing_id, expires)
+    )
+}
+
+pub fn verify(pairing_id: &str, expires: u64, signature: &str, now_secs: u64) -> bool {
+    verify_with(&signer().secret, pairing_id, expires, signature, now_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::types::MeetingLink;
+    use candid::Principal;
+
+    fn pairing() -> Pairing {
+        Pairing {
+            id: "pairing-1".to_string(),
+            user1: Principal::anonymous(),
+            user2: Principal::anonymous(),
+            country: "Kenya".to_string(),
+            date_created: 0,
+            status: PairingStatus::Active,
+            exchange: None,
+            meeting_link: Some(MeetingLink {
+                url: "https://meet.jit.si/pingpair-abc".to_string(),
+                expires_at: u64::MAX,
+            }),
+            meeting_time: Some(parse_utc("2026-10-20 15:00").unwrap() * NANOS_PER_SEC),
+            schedule_sequence: 2,
+            icebreakers: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn utc_round_trip() {
+        assert_eq!(format_utc(0), "19700101T000000Z");
+        assert_eq!(format_utc(parse_utc("2026-10-20T15:04:05Z").unwrap()), "20261020T150405Z");
+        assert_eq!(format_utc(parse_utc("2024-02-29 23:59").unwrap()), "20240229T235900Z");
+        assert!(parse_utc("2026-02-30 10:00").is_none());
+        assert!(parse_utc("2026-10-20").is_none());
+        assert!(parse_utc("2026-10-20 24:00").is_none());
+    }
+
+    #[test]
+    fn event_has_stable_uid_and_utc_times() {
+        let ics = render_calendar(&[(pairing(), None)], parse_utc("2026-10-19 09:00").unwrap());
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.contains("UID:pairing-1@pingpair\r\n"));
+        assert!(ics.contains("DTSTAMP:20261019T090000Z\r\n"));
+        assert!(ics.contains("DTSTART:20261020T150000Z\r\n"));
+        assert!(ics.contains("DTEND:20261020T153000Z\r\n"));
+        assert!(ics.contains("SEQUENCE:2\r\n"));
+        assert!(ics.contains("TRIGGER:-PT10M\r\n"));
+        assert!(ics.contains("Spotlight: Kenya"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= MAX_LINE_OCTETS));
+    }
+
+    #[test]
+    fn unscheduled_pairings_have_no_event() {
+        let mut pairing = pairing();
+        pairing.meeting_time = None;
+        assert!(pairing_event(&pairing, None, 0).is_none());
+    }
+
+    #[test]
+    fn signatures_are_bound_to_pairing_and_expiry() {
+        let secret = b"test-secret";
+        let signature = sign_with(secret, "pairing-1", 100);
+
+        assert!(verify_with(secret, "pairing-1", 100, &signature, 50));
+        assert!(!verify_with(secret, "pairing-1", 100, &signature, 101));
+        assert!(!verify_with(secret, "pairing-2", 100, &signature, 50));
+        assert!(!verify_with(secret, "pairing-1", 200, &signature, 50));
+        assert!(!verify_with(b"other", "pairing-1", 100, &signature, 50));
+    }
+}
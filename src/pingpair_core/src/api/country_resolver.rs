@@ -0,0 +1,288 @@
+use crate::model::catalogue;
+use crate::model::i18n::t;
+use crate::model::types::Country;
+
+// How many suggestions to offer for an unrecognised name
+const MAX_SUGGESTIONS: usize = 5;
+
+// How a query matched a country, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchKind {
+    IsoCode,
+    Name,
+    Alias,
+    NativeName,
+    Prefix,
+    Typo(usize),
+}
+
+impl MatchKind {
+    fn score(self) -> u32 {
+        match self {
+            MatchKind::IsoCode | MatchKind::Name => 100,
+            MatchKind::Alias | MatchKind::NativeName => 95,
+            MatchKind::Prefix => 70,
+            MatchKind::Typo(distance) => 60u32.saturating_sub(10 * distance as u32),
+        }
+    }
+
+    pub fn is_exact(self) -> bool {
+        self.score() >= MatchKind::Alias.score()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Candidate<'a> {
+    pub country: &'a Country,
+    pub kind: MatchKind,
+    pub score: u32,
+}
+
+#[derive(Debug)]
+pub enum Resolution<'a> {
+    Found(&'a Country),
+    // Several plausible countries, best first
+    Suggestions(Vec<&'a Country>),
+    NotFound,
+}
+
+// Fold a name for comparison: lowercase, Latin accents stripped, punctuation
+// dropped and a leading "the" ignored. "Côte d'Ivoire" becomes
+// "cote d ivoire".
+pub fn normalize(name: &str) -> String {
+    let mut folded = String::with_capacity(name.len());
+    for c in name.chars().flat_map(char::to_lowercase) {
+        match fold_accent(c) {
+            Some(replacement) => folded.push_str(replacement),
+            None if c.is_alphanumeric() => folded.push(c),
+            None if c == '&' => folded.push_str(" and "),
+            None => folded.push(' '),
+        }
+    }
+
+    let words: Vec<&str> = folded
+        .split_whitespace()
+        .map(|word| if word == "st" { "saint" } else { word })
+        .collect();
+    match words.split_first() {
+        Some((&"the", rest)) if !rest.is_empty() => rest.join(" "),
+        _ => words.join(" "),
+    }
+}
+
+fn fold_accent(c: char) -> Option<&'static str> {
+    let folded = match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ą' => "a",
+        'ç' | 'ć' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ę' | 'ě' => "e",
+        'ğ' => "g",
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'ı' => "i",
+        'ł' => "l",
+        'ñ' | 'ń' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => "o",
+        'ř' => "r",
+        'ś' | 'š' | 'ş' | 'ș' => "s",
+        'ť' | 'ţ' | 'ț' => "t",
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' => "u",
+        'ý' | 'ÿ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        'ß' => "ss",
+        'æ' => "ae",
+        'œ' => "oe",
+        'þ' => "th",
+        'ʻ' | '’' | '\'' => "",
+        _ => return None,
+    };
+    Some(folded)
+}
+
+// Levenshtein distance over characters
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+// Typos tolerated for a query of this length; short queries must be exact
+fn max_typos(query: &str) -> usize {
+    match query.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        8..=12 => 2,
+        _ => 3,
+    }
+}
+
+fn match_country(country: &Country, query: &str) -> Option<MatchKind> {
+    let is_code = query.len() <= 3 && query.chars().all(|c| c.is_ascii_alphabetic());
+    if is_code && (country.iso2.eq_ignore_ascii_case(query) || country.iso3.eq_ignore_ascii_case(query)) {
+        return Some(MatchKind::IsoCode);
+    }
+
+    let name = normalize(&country.name);
+    let aliases: Vec<String> = country.aliases.iter().map(|alias| normalize(alias)).collect();
+    let native_names: Vec<String> = country.native_names.iter().map(|alias| normalize(alias)).collect();
+
+    if name == query {
+        return Some(MatchKind::Name);
+    }
+    if aliases.iter().any(|alias| *alias == query) {
+        return Some(MatchKind::Alias);
+    }
+    if native_names.iter().any(|alias| *alias == query) {
+        return Some(MatchKind::NativeName);
+    }
+
+    let all_names = || std::iter::once(&name).chain(&aliases).chain(&native_names);
+
+    if query.chars().count() >= 3 && all_names().any(|candidate| candidate.starts_with(query)) {
+        return Some(MatchKind::Prefix);
+    }
+
+    all_names()
+        .map(|candidate| edit_distance(query, candidate))
+        .filter(|&distance| distance <= max_typos(query))
+        .min()
+        .map(MatchKind::Typo)
+}
+
+// Every country the query could refer to, best match first
+pub fn rank<'a>(countries: &'a [Country], query: &str) -> Vec<Candidate<'a>> {
+    let query = normalize(query);
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut candidates: Vec<Candidate<'a>> = countries
+        .iter()
+        .filter_map(|country| {
+            match_country(country, &query).map(|kind| Candidate {
+                country,
+                kind,
+                score: kind.score(),
+            })
+        })
+        .collect();
+
+    candidates.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| b.country.population.cmp(&a.country.population))
+            .then_with(|| a.country.name.cmp(&b.country.name))
+    });
+    candidates
+}
+
+// An exact match, or a prefix/typo match that beats every other candidate,
+// resolves the query. Anything else is a list of suggestions.
+pub fn resolve<'a>(countries: &'a [Country], query: &str) -> Resolution<'a> {
+    let candidates = rank(countries, query);
+
+    match candidates.as_slice() {
+        [] => Resolution::NotFound,
+        [best, ..] if best.kind.is_exact() => Resolution::Found(best.country),
+        [best] => Resolution::Found(best.country),
+        [best, second, ..] if best.score > second.score => Resolution::Found(best.country),
+        _ => Resolution::Suggestions(
+            candidates
+                .iter()
+                .take(MAX_SUGGESTIONS)
+                .map(|candidate| candidate.country)
+                .collect(),
+        ),
+    }
+}
+
+// Resolve against the loaded catalogue, with a user-facing message in
+// `locale` on failure
+pub fn resolve_country(query: &str, locale: &str) -> Result<&'static Country, String> {
+    match resolve(catalogue::catalogue().all(), query) {
+        Resolution::Found(country) => Ok(country),
+        Resolution::Suggestions(countries) => Err(t(locale, "resolver.suggestions", &[
+            ("query", query.trim().to_string()),
+            ("suggestions", countries
+                .iter()
+                .map(|country| format!("{} {}", country.flag, country.name))
+                .collect::<Vec<String>>()
+                .join(", ")),
+        ])),
+        Resolution::NotFound => Err(t(locale, "resolver.not_found", &[("query", query.trim().to_string())])),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn countries() -> &'static [Country] {
+        catalogue::catalogue().all()
+    }
+
+    fn resolved(query: &str) -> String {
+        match resolve(countries(), query) {
+            Resolution::Found(country) => country.name.clone(),
+            other => panic!("{} did not resolve: {:?}", query, other),
+        }
+    }
+
+    #[test]
+    fn codes_names_aliases_and_native_names_resolve() {
+        assert_eq!(resolved("ke"), "Kenya");
+        assert_eq!(resolved("DEU"), "Germany");
+        assert_eq!(resolved("united states of america"), "United States");
+        assert_eq!(resolved("UK"), "United Kingdom");
+        assert_eq!(resolved("Holland"), "Netherlands");
+        assert_eq!(resolved("Deutschland"), "Germany");
+        assert_eq!(resolved("espana"), "Spain");
+        assert_eq!(resolved("日本"), "Japan");
+        assert_eq!(resolved("cote d'ivoire"), "Côte d'Ivoire");
+        assert_eq!(resolved("The Gambia"), "Gambia");
+    }
+
+    #[test]
+    fn typos_resolve_when_unambiguous() {
+        assert_eq!(resolved("Germny"), "Germany");
+        assert_eq!(resolved("Argentinia"), "Argentina");
+        assert_eq!(resolved("Phillipines"), "Philippines");
+    }
+
+    #[test]
+    fn ambiguous_queries_are_ranked_suggestions() {
+        let ranked = rank(countries(), "Nigera");
+        let names: Vec<&str> = ranked.iter().map(|candidate| candidate.country.name.as_str()).collect();
+        assert!(names.starts_with(&["Nigeria", "Niger"]));
+
+        match resolve(countries(), "Nigera") {
+            Resolution::Suggestions(suggestions) => assert_eq!(suggestions[0].name, "Nigeria"),
+            other => panic!("expected suggestions, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unknown_names_are_not_invented() {
+        assert!(matches!(resolve(countries(), "Atlantis"), Resolution::NotFound));
+        assert!(resolve_country("Atlantis", "en").unwrap_err().contains("Atlantis"));
+        assert!(resolve_country("Sou", "en").unwrap_err().starts_with("I'm not sure which country **Sou** is. Did you mean"));
+        assert!(resolve_country("Sou", "fr").unwrap_err().starts_with("Je ne suis pas sûr du pays **Sou**."));
+    }
+
+    #[test]
+    fn edit_distance_counts_characters() {
+        assert_eq!(edit_distance("kenya", "kenya"), 0);
+        assert_eq!(edit_distance("kenia", "kenya"), 1);
+        assert_eq!(edit_distance("", "mali"), 4);
+        assert_eq!(edit_distance("türkiye", "turkiye"), 1);
+    }
+}
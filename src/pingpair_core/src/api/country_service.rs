@@ -0,0 +1,44 @@
+use crate::api::country_resolver;
+use crate::model::{catalogue, state};
+use crate::model::i18n::DEFAULT_LOCALE;
+use crate::model::spotlight::SpotlightPlanner;
+use crate::model::types::{Country, PingPairState};
+use std::collections::HashSet;
+
+// Look up a country by name, alias or ISO code and cache it in the state.
+// Unrecognised names are an error with suggestions, never a new country.
+pub async fn search_country_info(country_name: &str, state: &mut PingPairState) -> Result<Country, String> {
+    let country = country_resolver::resolve_country(country_name, DEFAULT_LOCALE)?;
+
+    // Check if country already exists in our database
+    if let Some(country) = state.countries.get(&country.name) {
+        return Ok(country.clone());
+    }
+
+    // Add the country to our database
+    state.countries.insert(country.name.clone(), country.clone());
+
+    Ok(country.clone())
+}
+
+// Countries that currently have active users, read from the availability
+// index the state store keeps up to date.
+pub fn get_available_countries() -> Vec<Country> {
+    let catalogue = catalogue::catalogue();
+    state::get_available_countries()
+        .iter()
+        .filter_map(|name| catalogue.get(name).cloned())
+        .map(|country| Country { available: true, ..country })
+        .collect()
+}
+
+// Next spotlight for a community. Countries with active users are featured
+// first; otherwise the planner rotates through the whole catalogue.
+pub fn get_country_spotlight(planner: &mut SpotlightPlanner, community: &str) -> Country {
+    let available: HashSet<String> = state::get_available_countries().into_iter().collect();
+
+    planner
+        .next_spotlight(community, catalogue::catalogue().all(), &available, 0)
+        .map(|spotlight| spotlight.country)
+        .expect("country catalogue is not empty")
+}
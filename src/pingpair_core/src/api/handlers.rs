@@ -0,0 +1,1081 @@
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use crate::metrics;
+use crate::api::{calendar, country_resolver, language_exchange, meeting, quiz, render, world_map};
+use crate::model::{catalogue, i18n, rng, state};
+use crate::model::availability::UNKNOWN_COUNTRY;
+use crate::model::i18n::{t, DEFAULT_LOCALE};
+use crate::model::spotlight::DEFAULT_COMMUNITY;
+use crate::model::types::{
+    UserProfile, Pairing, PairingStatus, Session, CommandResponse, BotCommand, IcebreakerQuestion, Proficiency, QuizProgress
+};
+
+// OpenChat bot types - similar to lib.rs but using model types
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct Message {
+    pub text: Option<String>,
+    pub sender: User,
+    pub timestamp: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct User {
+    pub id: String,
+    pub username: String,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct Context {
+    pub mentioned: bool,
+}
+
+// Message Handler
+pub async fn handle_message(
+    message: Message,
+    context: Context,
+) -> String {
+    let text = message.text.unwrap_or_default();
+    let locale = state::get_user_locale(&message.sender.id);
+    
+    if text.to_lowercase() == "hi" || text.to_lowercase() == "hello" {
+        return get_welcome_message(DEFAULT_COMMUNITY, &locale);
+    }
+    
+    t(&locale, "general.greeting", &[])
+}
+
+// Command Handler
+pub async fn handle_command(
+    message: Message,
+    context: Context,
+    command: String,
+    args: Vec<String>,
+) -> String {
+    let user_id = message.sender.id;
+    let username = message.sender.username;
+    
+    if command != "pingpair" {
+        return t(&state::get_user_locale(&user_id), "general.unknown_command", &[]);
+    }
+    
+    dispatch(user_id, username, args, DEFAULT_COMMUNITY)
+}
+
+// Subcommands `dispatch` knows, as used for metric labels
+pub const SUBCOMMANDS: [&str; 19] = [
+    "start", "profile", "skip", "stats", "timezone", "exchange", "language", "schedule", "quiz", "ping",
+    "map", "pick", "pair", "unpair", "done", "bio", "info", "networth", "icebreaker",
+];
+
+// A subcommand as a metric label: anything unknown is "other" so free text
+// cannot grow the number of series, and an empty one shows the help menu
+pub fn subcommand_label(subcommand: &str) -> &'static str {
+    let subcommand = subcommand.to_lowercase();
+    if subcommand.is_empty() {
+        return "help";
+    }
+    SUBCOMMANDS.iter().find(|&&known| known == subcommand).copied().unwrap_or("other")
+}
+
+// Route a `/pingpair` subcommand, given as its first argument, to its
+// handler. Every adapter (OpenChat bot, canister) goes through here.
+pub fn dispatch(user_id: String, username: String, args: Vec<String>, community: &str) -> String {
+    // Profiles and pairings are keyed by principal, so a malformed id stops
+    // here rather than in the store
+    let principal = match Principal::from_text(&user_id) {
+        Ok(principal) => principal,
+        Err(_) => return t(DEFAULT_LOCALE, "general.invalid_user", &[]),
+    };
+    let user_id = principal.to_text();
+    let locale = state::get_user_locale(&user_id);
+    
+    let subcommand = match args.first().filter(|subcommand| !subcommand.is_empty()) {
+        Some(subcommand) => subcommand.to_lowercase(),
+        None => return get_help_menu(community, &locale),
+    };
+    
+    match subcommand.as_str() {
+        "start" => handle_start_command(principal, username),
+        "profile" => handle_profile_command(principal, username, args, community),
+        "skip" => handle_skip_command(user_id, username),
+        "stats" => handle_stats_command(user_id, username, community),
+        "timezone" => handle_timezone_command(principal, username, args),
+        "exchange" => handle_exchange_command(principal, username, args),
+        "language" => handle_language_command(principal, username, args),
+        "schedule" => handle_schedule_command(user_id, args),
+        "quiz" => handle_quiz_command(principal, username, args),
+        "ping" => handle_ping_command(community, &locale),
+        _ => match parse_bot_command(&args) {
+            Some(command) => handle_bot_command(user_id, username, command, community),
+            None => t(&locale, "general.unknown_subcommand", &[]),
+        },
+    }
+}
+
+// Helper functions
+// Sent instead of the command's reply when `rate_limit` refuses it
+pub fn get_rate_limited_message(community: &str, locale: &str, retry_after_secs: u64) -> String {
+    render::render(community, locale, "general.rate_limited", &[("seconds", retry_after_secs.to_string())])
+}
+
+pub fn get_welcome_message(community: &str, locale: &str) -> String {
+    render::render(community, locale, "welcome.message", &[])
+}
+
+pub fn get_help_menu(community: &str, locale: &str) -> String {
+    render::render(community, locale, "help.menu", &[])
+}
+
+// Command implementations
+// The next spotlight in a community's rotation
+pub fn handle_ping_command(community: &str, locale: &str) -> String {
+    // Rotate through the catalogue without repeating countries or facts
+    let spotlight = match state::next_spotlight(community, 3) {
+        Some(spotlight) => spotlight,
+        None => return t(locale, "ping.none", &[]),
+    };
+
+    render::render(community, locale, "ping.spotlight", &[
+        ("country", spotlight.country.name),
+        ("flag", spotlight.country.flag),
+        ("facts", bullet_list(&spotlight.facts)),
+    ])
+}
+
+pub fn handle_start_command(principal: Principal, username: String) -> String {
+    let user_id = principal.to_text();
+    // Joining again after `skip` resumes matching
+    if let Some(user) = state::get_user(&user_id).filter(|user| !user.active) {
+        state::set_user_active(&user_id, true);
+        return t(&user.locale, "start.welcome_back", &[("name", username)]);
+    }
+    let user = state::get_or_create_user(principal, &username);
+
+    t(&user.locale, "start.joined", &[("name", username)])
+}
+
+pub fn handle_profile_command(principal: Principal, username: String, args: Vec<String>, community: &str) -> String {
+    if args.len() > 2 && args[1] == "country" {
+        return set_profile_country(principal, &username, &args[2..].join(" "));
+    }
+    let user_id = principal.to_text();
+    let locale = state::get_user_locale(&user_id);
+    if args.len() > 1 && args[1] == "update" {
+        return t(&locale, "profile.update", &[]);
+    }
+
+    let user = match state::get_user(&user_id) {
+        Some(user) => user,
+        None => return t(&locale, "general.not_joined", &[]),
+    };
+    let languages = user.languages
+        .iter()
+        .map(|skill| format!("{} ({})", skill.language, language_exchange::proficiency_label(skill.proficiency, &locale)))
+        .collect::<Vec<String>>();
+    let or_not_set = |value: &str| if value.is_empty() { t(&locale, "general.not_set", &[]) } else { value.to_string() };
+    let or_none_set = |values: &[String]| if values.is_empty() { t(&locale, "general.none_set", &[]) } else { values.join(", ") };
+
+    render::render(community, &locale, "profile.summary", &[
+        ("name", user.name.clone()),
+        ("country", or_not_set(if user.country == UNKNOWN_COUNTRY { "" } else { &user.country })),
+        ("timezone", or_not_set(&user.timezone)),
+        ("bio", or_not_set(&user.bio)),
+        ("interests", or_none_set(&user.interests)),
+        ("languages", or_none_set(&languages)),
+        ("visited", or_none_set(&user.countries_visited)),
+    ])
+}
+
+// Moving country updates the availability index through the store
+fn set_profile_country(user_id: Principal, username: &str, query: &str) -> String {
+    let mut user = state::get_or_create_user(user_id, username);
+    let country = match country_resolver::resolve_country(query, &user.locale) {
+        Ok(country) => country,
+        Err(message) => return message,
+    };
+
+    user.country = country.name.clone();
+    let reply = t(&user.locale, "profile.country_set", &[
+        ("country", country.name.clone()),
+        ("flag", country.flag.clone()),
+    ]);
+    state::update_user(&user_id.to_text(), user);
+
+    reply
+}
+
+pub fn handle_skip_command(user_id: String, username: String) -> String {
+    // Paused users leave the availability index until they resume
+    match state::set_user_active(&user_id, false) {
+        Some(user) => t(&user.locale, "skip.paused", &[]),
+        None => t(&state::get_user_locale(&user_id), "general.not_joined", &[]),
+    }
+}
+
+pub fn handle_stats_command(user_id: String, _username: String, community: &str) -> String {
+    let user = match state::get_user(&user_id) {
+        Some(user) => user,
+        None => return t(&state::get_user_locale(&user_id), "general.not_joined", &[]),
+    };
+    let locale = user.locale.as_str();
+
+    // Past pairings are archived in the order they ended
+    let completed: Vec<Pairing> = state::get_past_pairings()
+        .into_iter()
+        .filter(|pairing| pairing.status == PairingStatus::Completed)
+        .filter(|pairing| pairing.user1 == user.user_id || pairing.user2 == user.user_id)
+        .collect();
+
+    render::render(community, locale, "stats.summary", &[
+        ("score", user.net_worth.to_string()),
+        ("matches", completed.len().to_string()),
+        ("status", t(locale, if user.active { "stats.active" } else { "stats.paused" }, &[])),
+        ("last_match", completed.last().map(|pairing| pairing.country.clone()).unwrap_or_else(|| t(locale, "stats.no_matches", &[]))),
+        ("tier", calculate_strix_tier(user.net_worth, locale)),
+    ])
+}
+
+pub fn handle_timezone_command(principal: Principal, username: String, args: Vec<String>) -> String {
+    let user_id = principal.to_text();
+    if args.len() > 1 {
+        let mut user = state::get_or_create_user(principal, &username);
+        let locale = user.locale.clone();
+        let timezone = args[1..].join(" ");
+        user.timezone = timezone.clone();
+        state::update_user(&user_id, user);
+        return t(&locale, "timezone.updated", &[("timezone", timezone)]);
+    }
+
+    match state::get_user(&user_id) {
+        Some(user) => t(&user.locale, "timezone.current", &[("timezone", user.timezone)]),
+        None => t(&state::get_user_locale(&user_id), "general.not_joined", &[]),
+    }
+}
+
+pub fn handle_exchange_command(principal: Principal, username: String, args: Vec<String>) -> String {
+    let user_id = principal.to_text();
+    let action = args.get(1).map(|a| a.to_lowercase()).unwrap_or_default();
+
+    // Showing the settings or the help doesn't sign anyone up
+    let changes = matches!(action.as_str(), "on" | "off")
+        || (matches!(action.as_str(), "native" | "learn" | "remove") && args.len() > 2);
+    if !changes {
+        let locale = state::get_user_locale(&user_id);
+        return match state::get_user(&user_id) {
+            Some(user) if action.is_empty() => format_exchange_profile(&user),
+            None if action.is_empty() => t(&locale, "general.not_joined", &[]),
+            _ => t(&locale, "exchange.help", &[]),
+        };
+    }
+
+    let mut user = state::get_or_create_user(principal, &username);
+    let locale = user.locale.clone();
+    let reply = match action.as_str() {
+        "on" | "off" => {
+            user.language_exchange = action == "on";
+            if user.language_exchange {
+                t(&locale, "exchange.on", &[])
+            } else {
+                t(&locale, "exchange.off", &[])
+            }
+        }
+        "native" if args.len() > 2 => {
+            let language = language_exchange::set_language(&mut user, &args[2..].join(" "), Proficiency::Native);
+            t(&locale, "exchange.native_added", &[("language", language)])
+        }
+        "learn" if args.len() > 2 => {
+            // An optional trailing level, e.g. `learn Spanish intermediate`
+            let (language, level) = match language_exchange::parse_proficiency(&args[args.len() - 1]) {
+                Some(level) if args.len() > 3 => (args[2..args.len() - 1].join(" "), level),
+                _ => (args[2..].join(" "), Proficiency::Beginner),
+            };
+            if level == Proficiency::Native {
+                return t(&locale, "exchange.use_native", &[]);
+            }
+            let language = language_exchange::set_language(&mut user, &language, level);
+            t(&locale, "exchange.learning", &[
+                ("language", language),
+                ("level", language_exchange::proficiency_label(level, &locale)),
+            ])
+        }
+        "remove" if args.len() > 2 => {
+            let language = args[2..].join(" ");
+            let name = language_exchange::normalize_language(&language);
+            if !language_exchange::remove_language(&mut user, &language) {
+                return t(&locale, "exchange.not_on_profile", &[("language", name)]);
+            }
+            t(&locale, "exchange.removed", &[("language", name)])
+        }
+        _ => return t(&locale, "exchange.help", &[]),
+    };
+
+    state::update_user(&user_id, user);
+    reply
+}
+
+fn format_exchange_profile(user: &UserProfile) -> String {
+    let locale = user.locale.as_str();
+    let natives = language_exchange::native_languages(user);
+    let learning = language_exchange::learning_languages(user)
+        .into_iter()
+        .map(|skill| format!("{} ({})", skill.language, language_exchange::proficiency_label(skill.proficiency, locale)))
+        .collect::<Vec<String>>();
+
+    t(locale, "exchange.profile", &[
+        ("status", t(locale, if user.language_exchange { "exchange.status_on" } else { "exchange.status_off" }, &[])),
+        ("native", if natives.is_empty() { t(locale, "general.none_set", &[]) } else { natives.join(", ") }),
+        ("learning", if learning.is_empty() { t(locale, "general.none_set", &[]) } else { learning.join(", ") }),
+    ])
+}
+
+// `/pingpair language [code]` shows or changes the locale the bot replies in
+pub fn handle_language_command(principal: Principal, username: String, args: Vec<String>) -> String {
+    let user_id = principal.to_text();
+    let current = state::get_user_locale(&user_id);
+    let messages = i18n::messages();
+    let available = messages.locales()
+        .map(|locale| format!("{} (`{}`)", locale.name, locale.code))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    if args.len() < 2 {
+        let name = messages.find(&current)
+            .map(|locale| locale.name.clone())
+            .unwrap_or_else(|| current.clone());
+        return t(&current, "language.current", &[("language", name), ("available", available)]);
+    }
+
+    let requested = args[1..].join(" ");
+    let locale = match messages.find(&requested) {
+        Some(locale) => locale,
+        None => return t(&current, "language.unknown", &[("requested", requested), ("available", available)]),
+    };
+
+    let mut user = state::get_or_create_user(principal, &username);
+    user.locale = locale.code.clone();
+    state::update_user(&user_id, user);
+
+    // Confirm in the new language
+    t(&locale.code, "language.set", &[("language", locale.name.clone())])
+}
+
+// Parse the `BotCommand` actions from `/pingpair <subcommand> [args]`
+pub fn parse_bot_command(args: &[String]) -> Option<BotCommand> {
+    let subcommand = args.first()?.to_lowercase();
+    let rest = args[1..].join(" ");
+
+    match subcommand.as_str() {
+        "map" => Some(BotCommand::Map { personal: rest.eq_ignore_ascii_case("me") }),
+        "pick" if !rest.is_empty() => Some(BotCommand::Pick { country: rest }),
+        "pair" => Some(BotCommand::Pair),
+        "unpair" => Some(BotCommand::Unpair),
+        "done" => Some(BotCommand::Done),
+        "bio" => Some(BotCommand::Bio),
+        "info" => Some(BotCommand::Info { country: (!rest.is_empty()).then_some(rest) }),
+        "networth" => Some(BotCommand::NetWorth),
+        "icebreaker" => Some(BotCommand::Icebreaker),
+        _ => None,
+    }
+}
+
+pub fn handle_bot_command(user_id: String, _username: String, command: BotCommand, community: &str) -> String {
+    // Only `start` and commands that save something create a profile
+    let user = match state::get_user(&user_id) {
+        Some(user) => user,
+        None => return t(&state::get_user_locale(&user_id), "general.not_joined", &[]),
+    };
+
+    match command {
+        BotCommand::Map { personal } => handle_map_command(&user, personal),
+        BotCommand::Pick { country } => handle_pick_command(&user, &country),
+        BotCommand::Pair => handle_pair_command(&user, community),
+        BotCommand::Unpair => handle_unpair_command(&user),
+        BotCommand::Done => handle_done_command(&user),
+        BotCommand::Bio => handle_bio_command(&user),
+        BotCommand::Info { country } => handle_info_command(&user, country),
+        BotCommand::NetWorth => handle_networth_command(&user),
+        BotCommand::Icebreaker => handle_icebreaker_command(&user),
+    }
+}
+
+fn handle_map_command(user: &UserProfile, personal: bool) -> String {
+    let countries = catalogue::catalogue().all();
+
+    if personal {
+        return world_map::render_personal_map(user, countries);
+    }
+
+    let mut user_counts: HashMap<String, u32> = HashMap::new();
+    for user in state::get_users() {
+        *user_counts.entry(user.country).or_insert(0) += 1;
+    }
+
+    world_map::render_world_map(countries, &user_counts, &user.locale)
+}
+
+fn handle_pick_command(user: &UserProfile, country: &str) -> String {
+    let user_id = user.user_id.to_text();
+    let session = state::get_current_session().unwrap_or_else(start_session);
+
+    let country = match country_resolver::resolve_country(country, &user.locale) {
+        Ok(country) => country,
+        Err(message) => return message,
+    };
+
+    match session.featured_countries.iter().find(|c| c.eq_ignore_ascii_case(&country.name)) {
+        Some(featured) => {
+            state::set_session_pick(&user_id, featured);
+            t(&user.locale, "pick.picked", &[("country", featured.clone())])
+        }
+        None => t(&user.locale, "pick.not_featured", &[
+            ("country", country.name.clone()),
+            ("featured", session.featured_countries.join(", ")),
+        ]),
+    }
+}
+
+fn handle_pair_command(user: &UserProfile, community: &str) -> String {
+    let user_id = user.user_id.to_text();
+
+    if !user.active {
+        return t(&user.locale, "pair.paused", &[]);
+    }
+    if let Some(pairing) = state::get_active_pairing_for(&user_id) {
+        return t(&user.locale, "pair.already_paired", &[("country", pairing.country)]);
+    }
+
+    let paired = paired_users();
+    let pick = state::get_session_pick(&user_id);
+
+    let mut candidates: Vec<UserProfile> = state::get_users()
+        .into_iter()
+        .filter(|candidate| candidate.active && candidate.user_id != user.user_id && !paired.contains(&candidate.user_id))
+        .collect();
+
+    // Shuffle from a stable order so ties go to a random partner, and the
+    // same one for the same RNG seed.
+    candidates.sort_by_key(|candidate| candidate.user_id);
+    state::with_rng(|rng| rng::shuffle(rng, &mut candidates));
+
+    // Prefer a language-exchange partner, then someone who matches the picked
+    // country, then the most shared interests. Best first; among equals the
+    // last one shuffled.
+    candidates.reverse();
+    candidates.sort_by_cached_key(|candidate| {
+        let exchange = language_exchange::find_exchange(user, candidate).is_some();
+        let candidate_pick = state::get_session_pick(&candidate.user_id.to_text());
+        let picked = pick.as_deref() == Some(candidate.country.as_str())
+            || candidate_pick.as_deref() == Some(user.country.as_str())
+            || (pick.is_some() && pick == candidate_pick);
+        let shared_interests = candidate.interests
+            .iter()
+            .filter(|interest| user.interests.contains(interest))
+            .count();
+        Reverse((exchange, picked, shared_interests))
+    });
+
+    // Another command may pair a candidate, or the user, between the scan
+    // above and the insert; the store refuses those and the next one is tried
+    for partner in candidates {
+        let pairing = match language_exchange::find_exchange(user, &partner) {
+            Some(exchange) => {
+                let country = language_exchange::spotlight_for_exchange(&exchange, catalogue::catalogue().all())
+                    .map(|country| country.name)
+                    .unwrap_or_else(|| pairing_country(pick.clone(), &partner));
+                state::create_exchange_pairing(&user_id, &partner.user_id.to_text(), &country, exchange)
+            }
+            None => state::create_pairing(&user_id, &partner.user_id.to_text(), &pairing_country(pick.clone(), &partner)),
+        };
+
+        match pairing {
+            Ok(pairing) => {
+                return format!(
+                    "{}\n{}",
+                    t(&user.locale, "pair.partner", &[("name", partner.name)]),
+                    get_pairing_notification(&pairing, community, &user.locale)
+                );
+            }
+            Err(_) => {
+                if let Some(pairing) = state::get_active_pairing_for(&user_id) {
+                    return t(&user.locale, "pair.already_paired", &[("country", pairing.country)]);
+                }
+            }
+        }
+    }
+
+    t(&user.locale, "pair.no_partners", &[])
+}
+
+// The spotlight of a manual pairing: the user's pick, else the partner's
+// country, else the first featured country of the session, else the first
+// country of the catalogue.
+fn pairing_country(pick: Option<String>, partner: &UserProfile) -> String {
+    pick.or_else(|| (partner.country != UNKNOWN_COUNTRY).then(|| partner.country.clone()))
+        .or_else(|| state::get_current_session().and_then(|session| session.featured_countries.first().cloned()))
+        .or_else(|| catalogue::catalogue().all().first().map(|country| country.name.clone()))
+        .unwrap_or_else(|| UNKNOWN_COUNTRY.to_string())
+}
+
+fn handle_unpair_command(user: &UserProfile) -> String {
+    let user_id = user.user_id.to_text();
+    match state::get_active_pairing_for(&user_id).and_then(|pairing| state::cancel_pairing(&pairing.id)) {
+        Some(pairing) => t(&user.locale, "pair.left", &[("country", pairing.country)]),
+        None => t(&user.locale, "general.not_paired", &[]),
+    }
+}
+
+// Either partner marks the call as held, which completes the pairing for both
+fn handle_done_command(user: &UserProfile) -> String {
+    let user_id = user.user_id.to_text();
+    match state::get_active_pairing_for(&user_id).and_then(|pairing| state::complete_pairing(&pairing.id, STRIX_PER_PAIRING)) {
+        Some(pairing) => t(&user.locale, "pair.done", &[
+            ("country", pairing.country),
+            ("strix", STRIX_PER_PAIRING.to_string()),
+        ]),
+        None => t(&user.locale, "general.not_paired", &[]),
+    }
+}
+
+fn handle_bio_command(user: &UserProfile) -> String {
+    let locale = user.locale.as_str();
+    let pairing = match state::get_active_pairing_for(&user.user_id.to_text()) {
+        Some(pairing) => pairing,
+        None => return t(locale, "general.not_paired", &[]),
+    };
+
+    let partner_id = if pairing.user1 == user.user_id { pairing.user2 } else { pairing.user1 };
+    match state::get_user(&partner_id.to_text()) {
+        Some(partner) => t(locale, "bio.profile", &[
+            ("name", partner.name),
+            ("country", partner.country),
+            ("interests", if partner.interests.is_empty() { t(locale, "general.none_set", &[]) } else { partner.interests.join(", ") }),
+            ("bio", if partner.bio.is_empty() { t(locale, "bio.empty", &[]) } else { partner.bio }),
+        ]),
+        None => t(locale, "bio.unavailable", &[]),
+    }
+}
+
+fn handle_info_command(user: &UserProfile, country: Option<String>) -> String {
+    let user_id = user.user_id.to_text();
+
+    // Without a name, show the spotlight of the user's pairing or session
+    let name = country
+        .or_else(|| state::get_active_pairing_for(&user_id).map(|pairing| pairing.country))
+        .or_else(|| state::get_current_session().and_then(|session| session.featured_countries.first().cloned()));
+
+    let name = match name {
+        Some(name) => name,
+        None => return t(&user.locale, "info.which_country", &[]),
+    };
+
+    let country = match country_resolver::resolve_country(&name, &user.locale) {
+        Ok(country) => country,
+        Err(message) => return message,
+    };
+
+    t(&user.locale, "info.details", &[
+        ("country", country.name.clone()),
+        ("flag", country.flag.clone()),
+        ("continent", country.continent.clone()),
+        ("capital", country.capital.clone()),
+        ("population", country.population.to_string()),
+        ("currency", country.currency.clone()),
+        ("languages", country.languages.join(", ")),
+        ("facts", bullet_list(&country.fun_facts)),
+        ("traditions", bullet_list(&country.traditions)),
+    ])
+}
+
+fn handle_icebreaker_command(user: &UserProfile) -> String {
+    match state::next_icebreaker(&user.user_id.to_text()) {
+        Some(icebreaker) => t(&user.locale, "icebreaker.question", &[("question", icebreaker.question)]),
+        None => t(&user.locale, "icebreaker.exhausted", &[]),
+    }
+}
+
+fn bullet_list(items: &[String]) -> String {
+    items.iter()
+        .map(|item| format!("- {}", item))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// Strix awarded on joining and per completed pairing
+const STRIX_WELCOME_BONUS: u32 = 5;
+const STRIX_PER_PAIRING: u32 = 10;
+
+fn handle_networth_command(user: &UserProfile) -> String {
+    let completed = state::get_past_pairings()
+        .iter()
+        .filter(|pairing| pairing.status == PairingStatus::Completed)
+        .filter(|pairing| pairing.user1 == user.user_id || pairing.user2 == user.user_id)
+        .count() as u32;
+
+    // Attribute the balance to its known sources; anything left over came from
+    // other activity (profile updates, badges, ...)
+    let welcome = user.net_worth.min(STRIX_WELCOME_BONUS);
+    let pairings = (user.net_worth - welcome).min(completed * STRIX_PER_PAIRING);
+    let quiz = (user.net_worth - welcome - pairings).min(state::get_quiz_progress(&user.user_id.to_text()).strix_earned);
+    let other = user.net_worth - welcome - pairings - quiz;
+
+    t(&user.locale, "networth.summary", &[
+        ("total", user.net_worth.to_string()),
+        ("welcome", welcome.to_string()),
+        ("completed", completed.to_string()),
+        ("pairings", pairings.to_string()),
+        ("quiz", quiz.to_string()),
+        ("other", other.to_string()),
+        ("visited", user.countries_visited.len().to_string()),
+        ("badges", user.badges.len().to_string()),
+        ("tier", calculate_strix_tier(user.net_worth, &user.locale)),
+    ])
+}
+
+pub fn handle_quiz_command(principal: Principal, username: String, args: Vec<String>) -> String {
+    let user_id = principal.to_text();
+    let locale = state::get_user_locale(&user_id);
+    let mut progress = state::get_quiz_progress(&user_id);
+    let countries = catalogue::catalogue().all();
+    let action = args.get(1).map(|a| a.to_lowercase()).unwrap_or_default();
+
+    let reply = match action.as_str() {
+        "stats" => format_quiz_stats(&progress, &locale),
+        "" | "new" | "session" => {
+            if let Some(question) = &progress.pending {
+                return format!("{}\n{}", t(&locale, "quiz.still_open", &[]), quiz::format_question(question, &locale));
+            }
+
+            let question = if action == "session" {
+                let session = state::get_current_session().unwrap_or_else(start_session);
+                match state::with_rng(|rng| quiz::next_session_question(&mut progress, &session, countries, rng)) {
+                    Some(question) => question,
+                    None => {
+                        let reply = t(&locale, "quiz.session_finished", &[
+                            ("correct", progress.session_correct.to_string()),
+                            ("answered", progress.session_answered.len().to_string()),
+                        ]);
+                        state::set_quiz_progress(&user_id, progress);
+                        return reply;
+                    }
+                }
+            } else {
+                match state::with_rng(|rng| quiz::random_question(countries, rng)) {
+                    Some(question) => question,
+                    None => return t(&locale, "quiz.unavailable", &[]),
+                }
+            };
+
+            let reply = quiz::format_question(&question, &locale);
+            progress.pending = Some(question);
+            reply
+        }
+        _ => {
+            let question = match &progress.pending {
+                Some(question) => question,
+                None => return t(&locale, "quiz.no_open_question", &[]),
+            };
+            let choice = match quiz::parse_answer(&args[1..].join(" "), question) {
+                Some(choice) => choice,
+                None => return t(&locale, "quiz.answer_hint", &[]),
+            };
+
+            let outcome = quiz::grade(&mut progress, choice, state::time()).expect("question is open");
+            // Strix are kept on the profile, so answering signs the user up
+            let mut user = state::get_or_create_user(principal, &username);
+            user.net_worth += outcome.strix;
+            state::update_user(&user_id, user);
+
+            let streak = ("streak", outcome.streak.to_string());
+            match outcome.strix {
+                _ if !outcome.correct => t(&locale, "quiz.wrong", &[("answer", outcome.answer)]),
+                0 => t(&locale, "quiz.correct_capped", &[streak]),
+                strix => t(&locale, "quiz.correct", &[("strix", strix.to_string()), streak]),
+            }
+        }
+    };
+
+    state::set_quiz_progress(&user_id, progress);
+    reply
+}
+
+fn format_quiz_stats(progress: &QuizProgress, locale: &str) -> String {
+    t(locale, "quiz.stats", &[
+        ("answered", progress.answered.to_string()),
+        ("correct", progress.correct.to_string()),
+        ("streak", progress.streak.to_string()),
+        ("best_streak", progress.best_streak.to_string()),
+        ("earned", progress.strix_earned.to_string()),
+        ("today", progress.strix_today.to_string()),
+        ("cap", quiz::DAILY_STRIX_CAP.to_string()),
+    ])
+}
+
+pub fn handle_schedule_command(user_id: String, args: Vec<String>) -> String {
+    let locale = state::get_user_locale(&user_id);
+    let pairing = match state::get_active_pairing_for(&user_id) {
+        Some(pairing) => pairing,
+        None => return t(&locale, "general.not_paired", &[]),
+    };
+
+    let now_secs = state::time() / 1_000_000_000;
+    let meeting_time = match calendar::parse_utc(&args[1..].join(" ")) {
+        Some(secs) if secs > now_secs => secs,
+        Some(_) => return t(&locale, "schedule.past", &[]),
+        None => return t(&locale, "schedule.format", &[]),
+    };
+
+    let pairing = match state::schedule_pairing(&pairing.id, meeting_time * 1_000_000_000) {
+        Some(pairing) => pairing,
+        None => return t(&locale, "schedule.ended", &[]),
+    };
+    meeting::ensure_link(&pairing);
+
+    t(&locale, "schedule.scheduled", &[
+        ("country", pairing.country.clone()),
+        ("time", calendar::format_readable(meeting_time)),
+        ("url", calendar::signed_url(&pairing.id, now_secs)),
+    ])
+}
+
+// Users in an active pairing, who can't be matched again until it ends
+fn paired_users() -> HashSet<Principal> {
+    state::get_active_pairings()
+        .iter()
+        .flat_map(|pairing| [pairing.user1, pairing.user2])
+        .collect()
+}
+
+// Open a new session and match the language-exchange members for it
+pub fn start_session() -> Session {
+    let session = state::create_new_session();
+    match_users();
+    session
+}
+
+// Matching algorithm - new functionality
+pub fn match_users() -> Vec<Pairing> {
+    // Language-exchange users are matched first, on complementary languages,
+    // with a spotlight country where one of the languages is spoken.
+    // Remaining users will be grouped by timezone and interests.
+    let countries = catalogue::catalogue().all();
+    let timer = metrics::Timer::start();
+    let paired = paired_users();
+    let users: Vec<UserProfile> = state::get_users()
+        .into_iter()
+        .filter(|user| !paired.contains(&user.user_id))
+        .collect();
+    let candidates = users.iter().filter(|user| user.language_exchange && user.active).count();
+
+    // Pairings refused because a command paired one of the two meanwhile
+    // are skipped
+    let pairings: Vec<Pairing> = language_exchange::match_language_exchange(&users)
+        .into_iter()
+        .filter_map(|(user1, user2, exchange)| {
+            let country = language_exchange::spotlight_for_exchange(&exchange, countries)
+                .map(|country| country.name)
+                .unwrap_or_else(|| user1.country.clone());
+            state::create_exchange_pairing(
+                &user1.user_id.to_text(),
+                &user2.user_id.to_text(),
+                &country,
+                exchange,
+            )
+            .ok()
+        })
+        .collect();
+
+    metrics::MATCHER_DURATION.observe(&[], timer.seconds());
+    metrics::MATCHER_UNMATCHED.set(&[], candidates.saturating_sub(2 * pairings.len()) as f64);
+    pairings
+}
+
+// Notification sent to each partner, in their locale, when a pairing is made
+pub fn get_pairing_notification(pairing: &Pairing, community: &str, locale: &str) -> String {
+    let split = pairing.exchange
+        .as_ref()
+        .map(|exchange| format!("\n{}\n", language_exchange::format_exchange_split(exchange, locale)))
+        .unwrap_or_default();
+
+    let icebreakers = if pairing.icebreakers.is_empty() {
+        String::new()
+    } else {
+        let questions = pairing.icebreakers
+            .iter()
+            .map(|icebreaker| icebreaker.question.clone())
+            .collect::<Vec<String>>();
+        format!("\n{}\n{}\n", t(locale, "notification.icebreakers", &[]), bullet_list(&questions))
+    };
+
+    let link = meeting::ensure_link(pairing);
+    let valid_hours = link.expires_at.saturating_sub(state::time()) / 3_600_000_000_000;
+
+    render::render(community, locale, "notification.paired", &[
+        ("country", pairing.country.clone()),
+        ("details", format!("{}{}", split, icebreakers)),
+        ("url", link.url),
+        ("hours", valid_hours.to_string()),
+    ])
+}
+
+// Strix points calculator
+pub fn calculate_strix_tier(points: u32, locale: &str) -> String {
+    let tier = if points < 10 {
+        "tier.newcomer"
+    } else if points < 50 {
+        "tier.explorer"
+    } else if points < 100 {
+        "tier.connector"
+    } else if points < 200 {
+        "tier.networker"
+    } else {
+        "tier.ambassador"
+    };
+    t(locale, tier, &[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::clock::FakeClock;
+    use std::sync::Arc;
+
+    fn run(args: &[&str]) -> String {
+        run_as(1, "Amina", args)
+    }
+
+    fn run_as(id: u8, name: &str, args: &[&str]) -> String {
+        let user_id = Principal::from_slice(&[id]).to_text();
+        dispatch(user_id, name.to_string(), args.iter().map(|arg| arg.to_string()).collect(), DEFAULT_COMMUNITY)
+    }
+
+    #[test]
+    fn dispatch_routes_every_adapter_the_same_way() {
+        let _guard = state::testing::fresh_state(Arc::new(FakeClock::new(1_792_400_400 * 1_000_000_000)), 1);
+
+        assert_eq!(run(&[]), get_help_menu(DEFAULT_COMMUNITY, DEFAULT_LOCALE));
+        assert_eq!(run(&[""]), get_help_menu(DEFAULT_COMMUNITY, DEFAULT_LOCALE));
+        assert!(run(&["START"]).contains("Amina"));
+        assert!(run(&["ping"]).starts_with("# It's Ping Time! 🌍"));
+        assert_eq!(run(&["dance"]), t(DEFAULT_LOCALE, "general.unknown_subcommand", &[]));
+
+        run(&["language", "fr"]);
+        assert_eq!(run(&["dance"]), t("fr", "general.unknown_subcommand", &[]));
+    }
+
+    #[test]
+    fn a_new_session_matches_language_exchange_members_once() {
+        let _guard = state::testing::fresh_state(Arc::new(FakeClock::new(1_792_400_400 * 1_000_000_000)), 1);
+        let members = [
+            (1, "Amina", "English", "Spanish"),
+            (2, "Lucia", "Spanish", "English"),
+            (3, "Noa", "English", "Spanish"),
+            (4, "Mateo", "Spanish", "English"),
+        ];
+        for (id, name, native, learning) in members {
+            run_as(id, name, &["exchange", "on"]);
+            run_as(id, name, &["exchange", "native", native]);
+            run_as(id, name, &["exchange", "learn", learning]);
+        }
+        // Noa is already paired, so Mateo has nobody left to swap languages with
+        let noa = Principal::from_slice(&[3]).to_text();
+        state::create_pairing(&noa, &Principal::from_slice(&[5]).to_text(), "Kenya").unwrap();
+
+        run(&["pick", "Kenya"]);
+
+        let pairing = state::get_active_pairing_for(&Principal::from_slice(&[1]).to_text()).expect("Amina is matched");
+        assert_eq!(pairing.user2, Principal::from_slice(&[2]));
+        assert!(pairing.exchange.is_some());
+        let noa_pairings = state::get_active_pairings()
+            .into_iter()
+            .filter(|pairing| pairing.user1.to_text() == noa || pairing.user2.to_text() == noa)
+            .count();
+        assert_eq!(noa_pairings, 1);
+        assert!(state::get_active_pairing_for(&Principal::from_slice(&[4]).to_text()).is_none());
+
+        // Matching only runs when a session opens
+        run_as(2, "Lucia", &["unpair"]);
+        run(&["pick", "Kenya"]);
+        assert!(state::get_active_pairing_for(&Principal::from_slice(&[1]).to_text()).is_none());
+    }
+
+    #[test]
+    fn looking_around_does_not_sign_anyone_up() {
+        let _guard = state::testing::fresh_state(Arc::new(FakeClock::new(1_792_400_400 * 1_000_000_000)), 1);
+        let not_joined = t(DEFAULT_LOCALE, "general.not_joined", &[]);
+
+        for args in [&["map"][..], &["info", "Kenya"], &["networth"], &["bio"], &["pair"], &["exchange"], &["quiz", "stats"], &["language"]] {
+            let reply = run(args);
+            assert!(state::get_users().is_empty(), "{:?} created a profile", args);
+            if matches!(args[0], "map" | "info" | "networth" | "bio" | "pair" | "exchange") {
+                assert_eq!(reply, not_joined, "{:?}", args);
+            }
+        }
+
+        // Saving a setting does
+        run(&["language", "fr"]);
+        assert_eq!(state::get_user(&Principal::from_slice(&[1]).to_text()).unwrap().locale, "fr");
+    }
+
+    #[test]
+    fn malformed_user_ids_get_an_error_reply() {
+        let _guard = state::testing::fresh_state(Arc::new(FakeClock::new(1_792_400_400 * 1_000_000_000)), 1);
+        let invalid_user = t(DEFAULT_LOCALE, "general.invalid_user", &[]);
+
+        for args in [&["start"][..], &["timezone", "UTC+3"], &["pair"], &["quiz"]] {
+            let args = args.iter().map(|arg| arg.to_string()).collect();
+            assert_eq!(dispatch("not-a-principal".to_string(), "Amina".to_string(), args, DEFAULT_COMMUNITY), invalid_user);
+        }
+        assert!(state::get_users().is_empty());
+    }
+
+    #[test]
+    fn paused_users_cannot_pair() {
+        let _guard = state::testing::fresh_state(Arc::new(FakeClock::new(1_792_400_400 * 1_000_000_000)), 1);
+        run_as(2, "Kenji", &["start"]);
+        run(&["start"]);
+        run(&["skip"]);
+
+        assert_eq!(run(&["pair"]), t(DEFAULT_LOCALE, "pair.paused", &[]));
+        assert!(state::get_active_pairings().is_empty());
+
+        // Without a pick, a known partner country or a session, the pairing
+        // falls back to the catalogue
+        run(&["start"]);
+        assert!(run(&["pair"]).contains("Kenji"));
+        let pairing = state::get_active_pairing_for(&Principal::from_slice(&[1]).to_text()).unwrap();
+        assert_eq!(pairing.country, catalogue::catalogue().all()[0].name);
+    }
+
+    #[test]
+    fn a_completed_pairing_goes_on_both_maps() {
+        let _guard = state::testing::fresh_state(Arc::new(FakeClock::new(1_792_400_400 * 1_000_000_000)), 1);
+        run_as(2, "Kenji", &["start"]);
+        run(&["start"]);
+        assert_eq!(run(&["done"]), t(DEFAULT_LOCALE, "general.not_paired", &[]));
+
+        run(&["pair"]);
+        let country = state::get_active_pairing_for(&Principal::from_slice(&[1]).to_text()).unwrap().country;
+        assert!(!run(&["map", "me"]).contains("**Visited 1 of"));
+
+        // Either partner can mark the call as held
+        assert!(run_as(2, "Kenji", &["done"]).contains(&country));
+        assert!(state::get_active_pairings().is_empty());
+        assert_eq!(state::get_past_pairings()[0].status, PairingStatus::Completed);
+
+        for (id, name) in [(1, "Amina"), (2, "Kenji")] {
+            let map = run_as(id, name, &["map", "me"]);
+            assert!(map.contains("**Visited 1 of"), "{}", map);
+            assert!(map.contains(&country), "{}", map);
+            let user = state::get_user(&Principal::from_slice(&[id]).to_text()).unwrap();
+            assert_eq!(user.countries_visited, vec![country.clone()]);
+            assert_eq!(user.net_worth, STRIX_WELCOME_BONUS + STRIX_PER_PAIRING);
+        }
+        assert_eq!(run(&["done"]), t(DEFAULT_LOCALE, "general.not_paired", &[]));
+    }
+
+    #[test]
+    fn profile_and_stats_show_the_stored_profile() {
+        let _guard = state::testing::fresh_state(Arc::new(FakeClock::new(1_792_400_400 * 1_000_000_000)), 1);
+        assert_eq!(run(&["profile"]), t(DEFAULT_LOCALE, "general.not_joined", &[]));
+        assert_eq!(run(&["stats"]), t(DEFAULT_LOCALE, "general.not_joined", &[]));
+
+        run(&["start"]);
+        run_as(2, "Kenji", &["start"]);
+        run(&["profile", "country", "Kenya"]);
+        run(&["timezone", "UTC+3"]);
+        run(&["exchange", "native", "Swahili"]);
+        let user_id = Principal::from_slice(&[1]).to_text();
+        let mut user = state::get_user(&user_id).unwrap();
+        user.bio = "Runner and reader.".to_string();
+        user.interests = vec!["music".to_string(), "running".to_string()];
+        state::update_user(&user_id, user);
+
+        // Cancelled pairings don't count as matches
+        let cancelled = state::create_pairing(&user_id, &Principal::from_slice(&[2]).to_text(), "Brazil").unwrap();
+        state::cancel_pairing(&cancelled.id);
+        state::create_pairing(&user_id, &Principal::from_slice(&[2]).to_text(), "Japan").unwrap();
+        run(&["done"]);
+
+        assert_eq!(run(&["timezone"]), t(DEFAULT_LOCALE, "timezone.current", &[("timezone", "UTC+3".to_string())]));
+        assert_eq!(run(&["profile"]).trim_end(), include_str!("testdata/profile_en.golden").trim_end());
+        assert_eq!(run(&["stats"]).trim_end(), include_str!("testdata/stats_en.golden").trim_end());
+
+        run(&["skip"]);
+        assert!(run(&["stats"]).contains("**Status:** Paused"));
+    }
+
+    #[test]
+    fn meeting_links_last_until_the_scheduled_call_ends() {
+        let _guard = state::testing::fresh_state(Arc::new(FakeClock::new(1_792_400_400 * 1_000_000_000)), 1);
+        run_as(2, "Kenji", &["start"]);
+        run(&["start"]);
+        run(&["pair"]);
+        let pairing_id = state::get_active_pairing_for(&Principal::from_slice(&[1]).to_text()).unwrap().id;
+        let first = state::get_pairing(&pairing_id).unwrap().meeting_link.unwrap();
+
+        // Ten days out, well past the link's lifetime from today
+        run(&["schedule", "2026-10-29 15:00"]);
+        let pairing = state::get_pairing(&pairing_id).unwrap();
+        let link = pairing.meeting_link.unwrap();
+        let call_end = pairing.meeting_time.unwrap() + calendar::DEFAULT_DURATION_MINS * 60 * 1_000_000_000;
+        assert_ne!(link.url, first.url);
+        assert!(link.expires_at > call_end);
+
+        // Moving the call earlier keeps a link that still covers it
+        run(&["schedule", "2026-10-28 15:00"]);
+        assert_eq!(state::get_pairing(&pairing_id).unwrap().meeting_link.unwrap(), link);
+    }
+
+    #[test]
+    fn cancelling_a_scheduled_call_updates_the_calendar_event() {
+        let _guard = state::testing::fresh_state(Arc::new(FakeClock::new(1_792_400_400 * 1_000_000_000)), 1);
+        run_as(2, "Kenji", &["start"]);
+        run(&["start"]);
+        run(&["pair"]);
+        run(&["schedule", "2026-10-20 15:00"]);
+        let pairing_id = state::get_active_pairing_for(&Principal::from_slice(&[1]).to_text()).unwrap().id;
+        let scheduled = calendar::render_calendar(&[(state::get_pairing(&pairing_id).unwrap(), None)], 0);
+        assert!(scheduled.contains("SEQUENCE:0\r\n"));
+        assert!(scheduled.contains("STATUS:CONFIRMED\r\n"));
+
+        run(&["unpair"]);
+        let cancelled = calendar::render_calendar(&[(state::get_pairing(&pairing_id).unwrap(), None)], 0);
+        assert!(cancelled.contains("SEQUENCE:1\r\n"));
+        assert!(cancelled.contains("STATUS:CANCELLED\r\n"));
+    }
+
+    #[test]
+    fn concurrent_pair_commands_never_share_a_partner() {
+        let _guard = state::testing::fresh_state(Arc::new(FakeClock::new(1_792_400_400 * 1_000_000_000)), 1);
+        let members: Vec<u8> = (1..=8).collect();
+        for &id in &members {
+            run_as(id, "member", &["start"]);
+        }
+
+        std::thread::scope(|scope| {
+            for &id in &members {
+                scope.spawn(move || run_as(id, "member", &["pair"]));
+            }
+        });
+
+        let pairings = state::get_active_pairings();
+        let mut paired: Vec<Principal> = pairings.iter().flat_map(|pairing| [pairing.user1, pairing.user2]).collect();
+        paired.sort();
+        paired.dedup();
+        assert_eq!(paired.len(), 2 * pairings.len());
+        assert_eq!(pairings.len(), 4);
+    }
+
+    #[test]
+    fn subcommand_labels_are_bounded() {
+        assert_eq!(subcommand_label("Pair"), "pair");
+        assert_eq!(subcommand_label(""), "help");
+        assert_eq!(subcommand_label("<script>"), "other");
+        for subcommand in SUBCOMMANDS {
+            assert_eq!(subcommand_label(subcommand), subcommand);
+        }
+    }
+}
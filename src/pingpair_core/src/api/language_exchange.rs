@@ -0,0 +1,231 @@
+use crate::model::i18n::t;
+use crate::model::types::{Country, LanguageExchange, LanguageSkill, Proficiency, UserProfile};
+use std::collections::HashSet;
+
+// Default split suggested to language-exchange partners
+pub const DEFAULT_MINUTES_PER_LANGUAGE: u32 = 15;
+
+// Parse a proficiency level as typed by a user
+pub fn parse_proficiency(level: &str) -> Option<Proficiency> {
+    match level.to_lowercase().as_str() {
+        "beginner" | "a1" | "a2" => Some(Proficiency::Beginner),
+        "intermediate" | "b1" | "b2" => Some(Proficiency::Intermediate),
+        "advanced" | "c1" | "c2" => Some(Proficiency::Advanced),
+        "native" => Some(Proficiency::Native),
+        _ => None,
+    }
+}
+
+pub fn proficiency_label(proficiency: Proficiency, locale: &str) -> String {
+    let key = match proficiency {
+        Proficiency::Beginner => "exchange.level.beginner",
+        Proficiency::Intermediate => "exchange.level.intermediate",
+        Proficiency::Advanced => "exchange.level.advanced",
+        Proficiency::Native => "exchange.level.native",
+    };
+    t(locale, key, &[])
+}
+
+// Normalise a language name so "spanish" and "Spanish" are the same language
+pub fn normalize_language(language: &str) -> String {
+    let language = language.trim().to_lowercase();
+    let mut chars = language.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+pub fn native_languages(user: &UserProfile) -> Vec<&str> {
+    user.languages
+        .iter()
+        .filter(|skill| skill.proficiency == Proficiency::Native)
+        .map(|skill| skill.language.as_str())
+        .collect()
+}
+
+pub fn learning_languages(user: &UserProfile) -> Vec<&LanguageSkill> {
+    user.languages
+        .iter()
+        .filter(|skill| skill.proficiency != Proficiency::Native)
+        .collect()
+}
+
+// Add or replace a language on a user's profile
+pub fn set_language(user: &mut UserProfile, language: &str, proficiency: Proficiency) -> String {
+    let language = normalize_language(language);
+    user.languages.retain(|skill| skill.language != language);
+    user.languages.push(LanguageSkill {
+        language: language.clone(),
+        proficiency,
+    });
+    language
+}
+
+pub fn remove_language(user: &mut UserProfile, language: &str) -> bool {
+    let language = normalize_language(language);
+    let before = user.languages.len();
+    user.languages.retain(|skill| skill.language != language);
+    user.languages.len() != before
+}
+
+// Find the language one partner can teach which the other is learning.
+// Among several options, the learner with the lowest level gets priority.
+fn teachable(teacher: &UserProfile, learner: &UserProfile) -> Option<String> {
+    let natives = native_languages(teacher);
+    learning_languages(learner)
+        .into_iter()
+        .filter(|skill| natives.contains(&skill.language.as_str()))
+        .min_by_key(|skill| skill.proficiency)
+        .map(|skill| skill.language.clone())
+}
+
+// Two users complement each other when each speaks natively a language the
+// other is learning.
+pub fn find_exchange(user1: &UserProfile, user2: &UserProfile) -> Option<LanguageExchange> {
+    if !user1.language_exchange || !user2.language_exchange {
+        return None;
+    }
+
+    let user1_teaches = teachable(user1, user2)?;
+    let user2_teaches = teachable(user2, user1)?;
+
+    Some(LanguageExchange {
+        user1_teaches,
+        user2_teaches,
+        minutes_per_language: DEFAULT_MINUTES_PER_LANGUAGE,
+    })
+}
+
+// Greedily pair opted-in users with complementary languages. Users are
+// visited in join order so earlier members are matched first; ties are
+// broken by id so the result does not depend on map iteration order.
+pub fn match_language_exchange(users: &[UserProfile]) -> Vec<(UserProfile, UserProfile, LanguageExchange)> {
+    let mut candidates: Vec<&UserProfile> = users
+        .iter()
+        .filter(|user| user.language_exchange && user.active)
+        .collect();
+    candidates.sort_by_key(|user| (user.join_date, user.user_id));
+
+    let mut paired: HashSet<usize> = HashSet::new();
+    let mut matches = Vec::new();
+
+    for i in 0..candidates.len() {
+        if paired.contains(&i) {
+            continue;
+        }
+
+        for j in (i + 1)..candidates.len() {
+            if paired.contains(&j) {
+                continue;
+            }
+
+            if let Some(exchange) = find_exchange(candidates[i], candidates[j]) {
+                paired.insert(i);
+                paired.insert(j);
+                matches.push((candidates[i].clone(), candidates[j].clone(), exchange));
+                break;
+            }
+        }
+    }
+
+    matches
+}
+
+// Pick a spotlight country where one of the exchanged languages is spoken,
+// preferring countries that speak both.
+pub fn spotlight_for_exchange(exchange: &LanguageExchange, countries: &[Country]) -> Option<Country> {
+    let speaks = |country: &Country, language: &str| {
+        country.languages.iter().any(|l| l.eq_ignore_ascii_case(language))
+    };
+
+    countries
+        .iter()
+        .filter(|&country| speaks(country, &exchange.user1_teaches) || speaks(country, &exchange.user2_teaches))
+        .max_by_key(|&country| {
+            let both = speaks(country, &exchange.user1_teaches) && speaks(country, &exchange.user2_teaches);
+            (both, country.population)
+        })
+        .cloned()
+}
+
+// Suggested agenda for a language-exchange call
+pub fn format_exchange_split(exchange: &LanguageExchange, locale: &str) -> String {
+    t(locale, "exchange.split", &[
+        ("minutes", exchange.minutes_per_language.to_string()),
+        ("first", exchange.user1_teaches.clone()),
+        ("second", exchange.user2_teaches.clone()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::Principal;
+
+    fn user(id: u8, natives: &[&str], learning: &[(&str, Proficiency)]) -> UserProfile {
+        let mut languages: Vec<LanguageSkill> = natives
+            .iter()
+            .map(|l| LanguageSkill { language: l.to_string(), proficiency: Proficiency::Native })
+            .collect();
+        languages.extend(learning.iter().map(|(l, p)| LanguageSkill { language: l.to_string(), proficiency: *p }));
+
+        UserProfile {
+            user_id: Principal::from_slice(&[id]),
+            name: format!("user{}", id),
+            country: "Unknown".to_string(),
+            interests: Vec::new(),
+            bio: String::new(),
+            net_worth: 5,
+            badges: Vec::new(),
+            countries_visited: Vec::new(),
+            timezone: "UTC".to_string(),
+            languages,
+            language_exchange: true,
+            join_date: id as u64,
+            active: true,
+            locale: "en".to_string(),
+        }
+    }
+
+    #[test]
+    fn complementary_users_are_paired() {
+        let maria = user(1, &["Spanish"], &[("Swahili", Proficiency::Beginner)]);
+        let juma = user(2, &["Swahili"], &[("Spanish", Proficiency::Intermediate)]);
+
+        let exchange = find_exchange(&maria, &juma).unwrap();
+        assert_eq!(exchange.user1_teaches, "Spanish");
+        assert_eq!(exchange.user2_teaches, "Swahili");
+        assert_eq!(exchange.minutes_per_language, DEFAULT_MINUTES_PER_LANGUAGE);
+    }
+
+    #[test]
+    fn one_sided_interest_is_not_an_exchange() {
+        let maria = user(1, &["Spanish"], &[("Swahili", Proficiency::Beginner)]);
+        let kenji = user(2, &["Japanese"], &[("Spanish", Proficiency::Beginner)]);
+
+        assert!(find_exchange(&maria, &kenji).is_none());
+    }
+
+    #[test]
+    fn users_must_opt_in() {
+        let maria = user(1, &["Spanish"], &[("Swahili", Proficiency::Beginner)]);
+        let mut juma = user(2, &["Swahili"], &[("Spanish", Proficiency::Beginner)]);
+        juma.language_exchange = false;
+
+        assert!(match_language_exchange(&[maria, juma]).is_empty());
+    }
+
+    #[test]
+    fn spotlight_speaks_an_exchanged_language() {
+        let exchange = LanguageExchange {
+            user1_teaches: "Portuguese".to_string(),
+            user2_teaches: "Japanese".to_string(),
+            minutes_per_language: DEFAULT_MINUTES_PER_LANGUAGE,
+        };
+        let countries = crate::model::catalogue::catalogue().all();
+
+        let spotlight = spotlight_for_exchange(&exchange, countries).unwrap();
+        assert_eq!(spotlight.name, "Brazil");
+    }
+}
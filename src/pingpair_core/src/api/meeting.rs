@@ -0,0 +1,161 @@
+use crate::api::calendar;
+use crate::config::{MeetingConfig, MeetingProviderKind};
+use crate::model::state;
+use crate::model::types::{MeetingLink, Pairing};
+use std::sync::OnceLock;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+// Builds the video call link for a pairing
+pub trait MeetingProvider: Send + Sync {
+    fn create_link(&self, pairing_id: &str, now: u64) -> MeetingLink;
+}
+
+// Jitsi Meet, either the public instance or a self-hosted one
+pub struct JitsiProvider {
+    base_url: String,
+    link_ttl_secs: u64,
+}
+
+impl JitsiProvider {
+    pub fn new(base_url: &str, link_ttl_secs: u64) -> Self {
+        JitsiProvider {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            link_ttl_secs,
+        }
+    }
+}
+
+impl MeetingProvider for JitsiProvider {
+    fn create_link(&self, _pairing_id: &str, now: u64) -> MeetingLink {
+        MeetingLink {
+            url: format!("{}/{}", self.base_url, random_room_name()),
+            expires_at: now + self.link_ttl_secs * NANOS_PER_SEC,
+        }
+    }
+}
+
+// Any conferencing system reachable through a URL template. Supported
+// placeholders: `{room}` (random room name), `{pairing_id}` and
+// `{expires_at}` (unix seconds).
+pub struct TemplateProvider {
+    url_template: String,
+    link_ttl_secs: u64,
+}
+
+impl TemplateProvider {
+    pub fn new(url_template: &str, link_ttl_secs: u64) -> Result<Self, String> {
+        // Without a random component the link would be guessable
+        if !url_template.contains("{room}") {
+            return Err(format!("Meeting URL template must contain {{room}}: {}", url_template));
+        }
+
+        Ok(TemplateProvider {
+            url_template: url_template.to_string(),
+            link_ttl_secs,
+        })
+    }
+}
+
+impl MeetingProvider for TemplateProvider {
+    fn create_link(&self, pairing_id: &str, now: u64) -> MeetingLink {
+        let expires_at = now + self.link_ttl_secs * NANOS_PER_SEC;
+
+        MeetingLink {
+            url: self.url_template
+                .replace("{room}", &random_room_name())
+                .replace("{pairing_id}", pairing_id)
+                .replace("{expires_at}", &(expires_at / NANOS_PER_SEC).to_string()),
+            expires_at,
+        }
+    }
+}
+
+// 128 bits from the OS CSPRNG, hex encoded
+pub fn random_room_name() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).expect("OS random number generator unavailable");
+
+    let suffix: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("pingpair-{}", suffix)
+}
+
+static PROVIDER: OnceLock<Box<dyn MeetingProvider>> = OnceLock::new();
+
+pub fn build_provider(config: &MeetingConfig) -> Result<Box<dyn MeetingProvider>, String> {
+    match config.provider {
+        MeetingProviderKind::Jitsi => Ok(Box::new(JitsiProvider::new(&config.base_url, config.link_ttl_secs))),
+        MeetingProviderKind::Template => {
+            let template = config.url_template
+                .as_deref()
+                .ok_or("Meeting provider \"template\" requires url_template")?;
+            Ok(Box::new(TemplateProvider::new(template, config.link_ttl_secs)?))
+        }
+    }
+}
+
+// Install the provider from config; call once at startup
+pub fn configure(config: &MeetingConfig) -> Result<(), String> {
+    let provider = build_provider(config)?;
+    PROVIDER
+        .set(provider)
+        .map_err(|_| "Meeting provider already configured".to_string())
+}
+
+pub fn provider() -> &'static dyn MeetingProvider {
+    PROVIDER
+        .get_or_init(|| build_provider(&MeetingConfig::default()).expect("default meeting provider"))
+        .as_ref()
+}
+
+// The pairing's meeting link, creating (or replacing an expired) one and
+// storing it on the pairing. Once a call is scheduled the link has to last
+// until it ends, so its lifetime starts at the meeting time.
+pub fn ensure_link(pairing: &Pairing) -> MeetingLink {
+    let now = state::time();
+    let start = pairing.meeting_time.map_or(now, |meeting_time| meeting_time.max(now));
+    let needed_until = match pairing.meeting_time {
+        Some(_) => start + calendar::DEFAULT_DURATION_MINS * 60 * NANOS_PER_SEC,
+        None => now,
+    };
+
+    match &pairing.meeting_link {
+        Some(link) if link.expires_at > needed_until => link.clone(),
+        _ => {
+            let link = provider().create_link(&pairing.id, start);
+            state::set_meeting_link(&pairing.id, link.clone());
+            link
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jitsi_rooms_are_unique_and_unguessable() {
+        let provider = JitsiProvider::new("https://meet.example.org/", 60);
+        let first = provider.create_link("pairing-1", 0);
+        let second = provider.create_link("pairing-1", 0);
+
+        assert_ne!(first.url, second.url);
+        assert!(first.url.starts_with("https://meet.example.org/pingpair-"));
+        assert_eq!(first.url.len(), "https://meet.example.org/pingpair-".len() + 32);
+        assert_eq!(first.expires_at, 60 * NANOS_PER_SEC);
+    }
+
+    #[test]
+    fn template_placeholders_are_filled() {
+        let provider = TemplateProvider::new("https://video.example.com/r/{room}?ref={pairing_id}&exp={expires_at}", 10).unwrap();
+        let link = provider.create_link("pairing-42", 5 * NANOS_PER_SEC);
+
+        assert!(link.url.starts_with("https://video.example.com/r/pingpair-"));
+        assert!(link.url.ends_with("?ref=pairing-42&exp=15"));
+    }
+
+    #[test]
+    fn template_without_room_is_rejected() {
+        assert!(TemplateProvider::new("https://video.example.com/{pairing_id}", 10).is_err());
+    }
+}
@@ -1,11 +1,19 @@
 pub mod handlers;
+pub mod calendar;
+pub mod country_resolver;
 pub mod country_service;
+pub mod language_exchange;
+pub mod meeting;
+pub mod queries;
+pub mod quiz;
+pub mod rate_limit;
+pub mod render;
+pub mod world_map;
 
 // Re-export key functions
 pub use handlers::{handle_message, handle_command, get_help_menu};
 pub use country_service::{
     search_country_info, 
-    update_country_availability, 
     get_available_countries,
     get_country_spotlight
 }; 
\ No newline at end of file
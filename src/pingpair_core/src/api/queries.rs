@@ -0,0 +1,189 @@
+use crate::model::state;
+use crate::model::types::{BotStats, LeaderboardEntry, Pairing, PairingStatus, Session, UserProfile};
+use candid::Principal;
+
+// Largest leaderboard page a caller can ask for
+pub const MAX_LEADERBOARD_PAGE: u32 = 100;
+
+// Read-only views of the store for dashboards and other canisters. Anyone
+// may read them, but a profile's private fields are only shown to its owner
+// and a pairing's call details only to its two partners.
+
+// Timezone, locale and languages are private to the profile's owner
+pub fn get_profile(caller: Principal, user_id: Principal) -> Option<UserProfile> {
+    let profile = state::get_user(&user_id.to_text())?;
+    Some(if caller == user_id { profile } else { public_profile(profile) })
+}
+
+fn public_profile(profile: UserProfile) -> UserProfile {
+    UserProfile {
+        timezone: String::new(),
+        locale: String::new(),
+        languages: Vec::new(),
+        language_exchange: false,
+        ..profile
+    }
+}
+
+// Users by Strix score, earliest joiner first on a tie
+pub fn get_leaderboard(limit: u32, offset: u32) -> Vec<LeaderboardEntry> {
+    let mut users = state::get_users();
+    users.sort_by(|a, b| {
+        b.net_worth
+            .cmp(&a.net_worth)
+            .then(a.join_date.cmp(&b.join_date))
+            .then(a.user_id.cmp(&b.user_id))
+    });
+
+    users
+        .into_iter()
+        .enumerate()
+        .skip(offset as usize)
+        .take(limit.min(MAX_LEADERBOARD_PAGE) as usize)
+        .map(|(index, user)| LeaderboardEntry {
+            rank: index as u32 + 1,
+            user_id: user.user_id,
+            name: user.name,
+            country: user.country,
+            net_worth: user.net_worth,
+        })
+        .collect()
+}
+
+pub fn get_current_session(caller: Principal) -> Option<Session> {
+    let mut session = state::get_current_session()?;
+    session.pairings = session.pairings.into_iter().map(|pairing| visible_pairing(caller, pairing)).collect();
+    Some(session)
+}
+
+pub fn get_pairing(caller: Principal, pairing_id: &str) -> Option<Pairing> {
+    state::get_pairing(pairing_id).map(|pairing| visible_pairing(caller, pairing))
+}
+
+// The call link, time, icebreakers and exchange split are for the partners
+fn visible_pairing(caller: Principal, pairing: Pairing) -> Pairing {
+    if caller == pairing.user1 || caller == pairing.user2 {
+        return pairing;
+    }
+
+    Pairing {
+        exchange: None,
+        meeting_link: None,
+        meeting_time: None,
+        icebreakers: Vec::new(),
+        ..pairing
+    }
+}
+
+pub fn get_stats() -> BotStats {
+    let users = state::get_users();
+
+    BotStats {
+        users: users.len() as u64,
+        active_users: users.iter().filter(|user| user.active).count() as u64,
+        countries: state::get_available_countries().len() as u64,
+        active_pairings: state::get_active_pairings().len() as u64,
+        completed_pairings: state::get_past_pairings()
+            .iter()
+            .filter(|pairing| pairing.status == PairingStatus::Completed)
+            .count() as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::clock::FakeClock;
+    use crate::model::state::testing;
+    use crate::model::types::MeetingLink;
+    use std::sync::Arc;
+
+    // 2026-10-19T09:00:00Z
+    const START: u64 = 1_792_400_400 * 1_000_000_000;
+
+    fn principal(id: u8) -> Principal {
+        Principal::from_slice(&[id])
+    }
+
+    fn join(id: u8, name: &str, net_worth: u32) {
+        let mut user = state::create_user(principal(id), name.to_string());
+        user.net_worth = net_worth;
+        user.timezone = "UTC+3".to_string();
+        user.join_date = id as u64;
+        state::update_user(&principal(id).to_text(), user);
+    }
+
+    #[test]
+    fn only_owners_see_private_profile_fields() {
+        let _guard = testing::fresh_state(Arc::new(FakeClock::new(START)), 1);
+        join(1, "Amina", 5);
+
+        let own = get_profile(principal(1), principal(1)).unwrap();
+        assert_eq!(own.timezone, "UTC+3");
+        assert_eq!(own.locale, "en");
+
+        let other = get_profile(principal(2), principal(1)).unwrap();
+        assert_eq!(other.name, "Amina");
+        assert_eq!(other.timezone, "");
+        assert_eq!(other.locale, "");
+        assert!(get_profile(principal(1), principal(9)).is_none());
+    }
+
+    #[test]
+    fn only_partners_see_call_details() {
+        let _guard = testing::fresh_state(Arc::new(FakeClock::new(START)), 1);
+        join(1, "Amina", 5);
+        join(2, "Kenji", 5);
+        let pairing = state::create_pairing(&principal(1).to_text(), &principal(2).to_text(), "Kenya").unwrap();
+        state::set_meeting_link(&pairing.id, MeetingLink {
+            url: "https://meet.jit.si/pingpair-abc".to_string(),
+            expires_at: START,
+        });
+
+        assert!(get_pairing(principal(2), &pairing.id).unwrap().meeting_link.is_some());
+        let seen = get_pairing(principal(3), &pairing.id).unwrap();
+        assert_eq!(seen.country, "Kenya");
+        assert!(seen.meeting_link.is_none());
+        assert!(get_pairing(principal(1), "missing").is_none());
+    }
+
+    #[test]
+    fn leaderboard_pages_by_strix_score() {
+        let _guard = testing::fresh_state(Arc::new(FakeClock::new(START)), 1);
+        join(1, "Amina", 20);
+        join(2, "Kenji", 40);
+        join(3, "Lucia", 20);
+
+        let names = |entries: Vec<LeaderboardEntry>| {
+            entries.into_iter().map(|entry| (entry.rank, entry.name)).collect::<Vec<(u32, String)>>()
+        };
+        assert_eq!(
+            names(get_leaderboard(10, 0)),
+            vec![(1, "Kenji".to_string()), (2, "Amina".to_string()), (3, "Lucia".to_string())]
+        );
+        assert_eq!(names(get_leaderboard(1, 2)), vec![(3, "Lucia".to_string())]);
+        assert!(get_leaderboard(10, 3).is_empty());
+
+        let stats = get_stats();
+        assert_eq!(stats.users, 3);
+        assert_eq!(stats.active_users, 3);
+        assert_eq!(stats.active_pairings, 0);
+    }
+
+    #[test]
+    fn stats_count_only_completed_pairings() {
+        let _guard = testing::fresh_state(Arc::new(FakeClock::new(START)), 1);
+        join(1, "Amina", 5);
+        join(2, "Kenji", 5);
+        join(3, "Lucia", 5);
+        let cancelled = state::create_pairing(&principal(1).to_text(), &principal(2).to_text(), "Kenya").unwrap();
+        state::cancel_pairing(&cancelled.id);
+        let completed = state::create_pairing(&principal(1).to_text(), &principal(2).to_text(), "Japan").unwrap();
+        state::complete_pairing(&completed.id, 10);
+        state::create_pairing(&principal(1).to_text(), &principal(3).to_text(), "Brazil").unwrap();
+
+        let stats = get_stats();
+        assert_eq!(stats.active_pairings, 1);
+        assert_eq!(stats.completed_pairings, 1);
+    }
+}
@@ -0,0 +1,336 @@
+use crate::model::catalogue::{self, CONTINENTS};
+use crate::model::i18n::t;
+use crate::model::rng::{self, Rng};
+use crate::model::types::{Country, QuizProgress, QuizQuestion, QuizTopic, Session};
+use std::collections::HashSet;
+
+pub const CHOICES: usize = 4;
+pub const STRIX_PER_CORRECT_ANSWER: u32 = 2;
+pub const DAILY_STRIX_CAP: u32 = 10;
+
+const NANOS_PER_DAY: u64 = 24 * 60 * 60 * 1_000_000_000;
+const TOPICS: [QuizTopic; 5] = [
+    QuizTopic::Capital,
+    QuizTopic::Currency,
+    QuizTopic::Language,
+    QuizTopic::Tradition,
+    QuizTopic::Continent,
+];
+const LETTERS: [&str; CHOICES] = ["A", "B", "C", "D"];
+
+// Every answer that would be right for this country and topic
+fn answers(country: &Country, topic: QuizTopic) -> Vec<String> {
+    match topic {
+        QuizTopic::Capital => vec![country.capital.clone()],
+        QuizTopic::Currency => vec![country.currency.clone()],
+        QuizTopic::Language => country.languages.clone(),
+        QuizTopic::Tradition => country.traditions.clone(),
+        QuizTopic::Continent => vec![country.continent.clone()],
+    }
+}
+
+// The question is worded when it is shown, in the reader's locale
+fn prompt(question: &QuizQuestion, locale: &str) -> String {
+    let key = match question.topic {
+        QuizTopic::Capital => "quiz.prompt.capital",
+        QuizTopic::Currency => "quiz.prompt.currency",
+        QuizTopic::Language => "quiz.prompt.language",
+        QuizTopic::Tradition => "quiz.prompt.tradition",
+        QuizTopic::Continent => "quiz.prompt.continent",
+    };
+    let flag = catalogue::catalogue()
+        .get(&question.country)
+        .map(|country| country.flag.clone())
+        .unwrap_or_default();
+
+    t(locale, key, &[("country", question.country.clone()), ("flag", flag)])
+}
+
+// Build a question about `country`. Distractors come from other countries,
+// neighbours on the same continent first so the wrong answers are
+// plausible; none of them is also a right answer.
+pub fn generate_question(
+    countries: &[Country],
+    country: &Country,
+    topic: QuizTopic,
+    rng: &mut dyn Rng,
+) -> Option<QuizQuestion> {
+    let right = answers(country, topic);
+    if right.is_empty() {
+        return None;
+    }
+    let answer = right[rng.below(right.len())].clone();
+
+    let mut near: Vec<String> = Vec::new();
+    let mut far: Vec<String> = Vec::new();
+    if topic == QuizTopic::Continent {
+        far.extend(CONTINENTS.iter().map(|continent| continent.to_string()));
+    } else {
+        for other in countries.iter().filter(|other| other.name != country.name) {
+            let pool = if other.continent == country.continent { &mut near } else { &mut far };
+            pool.extend(answers(other, topic));
+        }
+    }
+    rng::shuffle(rng, &mut near);
+    rng::shuffle(rng, &mut far);
+
+    let mut seen: HashSet<String> = right.iter().map(|value| value.to_lowercase()).collect();
+    let distractors: Vec<String> = near
+        .into_iter()
+        .chain(far)
+        .filter(|value| seen.insert(value.to_lowercase()))
+        .take(CHOICES - 1)
+        .collect();
+    if distractors.len() < CHOICES - 1 {
+        return None;
+    }
+
+    let mut choices = distractors;
+    choices.push(answer.clone());
+    rng::shuffle(rng, &mut choices);
+    let answer = choices.iter().position(|choice| *choice == answer)? as u32;
+
+    Some(QuizQuestion {
+        country: country.name.clone(),
+        topic,
+        choices,
+        answer,
+        session_id: None,
+    })
+}
+
+// A question on a random topic about `country`
+pub fn question_about(countries: &[Country], country: &Country, rng: &mut dyn Rng) -> Option<QuizQuestion> {
+    let mut topics = TOPICS.to_vec();
+    rng::shuffle(rng, &mut topics);
+    topics
+        .into_iter()
+        .find_map(|topic| generate_question(countries, country, topic, rng))
+}
+
+pub fn random_question(countries: &[Country], rng: &mut dyn Rng) -> Option<QuizQuestion> {
+    if countries.is_empty() {
+        return None;
+    }
+    let country = &countries[rng.below(countries.len())];
+    question_about(countries, country, rng)
+}
+
+// The next featured country of the session this user hasn't been quizzed on
+pub fn next_session_question(
+    progress: &mut QuizProgress,
+    session: &Session,
+    countries: &[Country],
+    rng: &mut dyn Rng,
+) -> Option<QuizQuestion> {
+    if progress.session_id.as_deref() != Some(session.id.as_str()) {
+        progress.session_id = Some(session.id.clone());
+        progress.session_answered.clear();
+        progress.session_correct = 0;
+    }
+
+    let country = session.featured_countries
+        .iter()
+        .filter(|name| !progress.session_answered.contains(name))
+        .find_map(|name| countries.iter().find(|country| country.name == *name))?;
+
+    let mut question = question_about(countries, country, rng)?;
+    question.session_id = Some(session.id.clone());
+    Some(question)
+}
+
+// "b", "B", "2" or the text of a choice
+pub fn parse_answer(input: &str, question: &QuizQuestion) -> Option<usize> {
+    let input = input.trim();
+    if let Some(index) = LETTERS.iter().position(|letter| letter.eq_ignore_ascii_case(input)) {
+        return (index < question.choices.len()).then_some(index);
+    }
+    if let Ok(number) = input.parse::<usize>() {
+        return (1..=question.choices.len()).contains(&number).then(|| number - 1);
+    }
+    question.choices
+        .iter()
+        .position(|choice| choice.eq_ignore_ascii_case(input))
+}
+
+#[derive(Debug, PartialEq)]
+pub struct QuizOutcome {
+    pub correct: bool,
+    pub answer: String,
+    pub strix: u32,
+    pub streak: u32,
+}
+
+// Grade the pending question. Strix stop at the daily cap but streaks and
+// scores keep counting.
+pub fn grade(progress: &mut QuizProgress, choice: usize, now: u64) -> Option<QuizOutcome> {
+    let question = progress.pending.take()?;
+    let correct = choice == question.answer as usize;
+
+    let today = now / NANOS_PER_DAY;
+    if progress.strix_day != today {
+        progress.strix_day = today;
+        progress.strix_today = 0;
+    }
+
+    progress.answered += 1;
+    let strix = if correct {
+        progress.correct += 1;
+        progress.streak += 1;
+        progress.best_streak = progress.best_streak.max(progress.streak);
+        STRIX_PER_CORRECT_ANSWER.min(DAILY_STRIX_CAP - progress.strix_today)
+    } else {
+        progress.streak = 0;
+        0
+    };
+    progress.strix_today += strix;
+    progress.strix_earned += strix;
+
+    if question.session_id.is_some() && question.session_id == progress.session_id {
+        progress.session_answered.push(question.country.clone());
+        if correct {
+            progress.session_correct += 1;
+        }
+    }
+
+    Some(QuizOutcome {
+        correct,
+        answer: question.choices[question.answer as usize].clone(),
+        strix,
+        streak: progress.streak,
+    })
+}
+
+pub fn format_question(question: &QuizQuestion, locale: &str) -> String {
+    let choices = question.choices
+        .iter()
+        .zip(LETTERS)
+        .map(|(choice, letter)| format!("**{}.** {}", letter, choice))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    t(locale, "quiz.question", &[("prompt", prompt(question, locale)), ("choices", choices)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::catalogue;
+    use crate::model::rng::SeededRng;
+
+    fn countries() -> &'static [Country] {
+        catalogue::catalogue().all()
+    }
+
+    fn pending(answer: u32) -> QuizProgress {
+        QuizProgress {
+            pending: Some(QuizQuestion {
+                country: "Kenya".to_string(),
+                topic: QuizTopic::Capital,
+                choices: vec!["Nairobi".to_string(), "Kampala".to_string(), "Dodoma".to_string(), "Kigali".to_string()],
+                answer,
+                session_id: None,
+            }),
+            ..QuizProgress::default()
+        }
+    }
+
+    #[test]
+    fn every_country_and_topic_has_a_fair_question() {
+        let mut rng = SeededRng::new(1);
+
+        for country in countries() {
+            for topic in TOPICS {
+                let question = generate_question(countries(), country, topic, &mut rng).unwrap();
+                let right = answers(country, topic);
+
+                assert_eq!(question.choices.len(), CHOICES);
+                assert!(right.contains(&question.choices[question.answer as usize]));
+                let distinct: HashSet<String> = question.choices.iter().map(|c| c.to_lowercase()).collect();
+                assert_eq!(distinct.len(), CHOICES, "{:?}", question);
+                for (i, choice) in question.choices.iter().enumerate() {
+                    if i != question.answer as usize {
+                        assert!(!right.iter().any(|r| r.eq_ignore_ascii_case(choice)), "{:?}", question);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn capital_distractors_come_from_the_same_continent() {
+        let kenya = catalogue::catalogue().get("Kenya").unwrap();
+        let question = generate_question(countries(), kenya, QuizTopic::Capital, &mut SeededRng::new(5)).unwrap();
+
+        for choice in &question.choices {
+            let owner = countries().iter().find(|country| country.capital == *choice).unwrap();
+            assert_eq!(owner.continent, "Africa");
+        }
+    }
+
+    #[test]
+    fn streaks_and_daily_cap() {
+        let day = NANOS_PER_DAY;
+        let mut progress = QuizProgress::default();
+
+        for _ in 0..6 {
+            progress.pending = pending(0).pending;
+            grade(&mut progress, 0, day).unwrap();
+        }
+        assert_eq!(progress.streak, 6);
+        assert_eq!(progress.strix_today, DAILY_STRIX_CAP);
+        assert_eq!(progress.strix_earned, DAILY_STRIX_CAP);
+
+        progress.pending = pending(0).pending;
+        let wrong = grade(&mut progress, 2, day).unwrap();
+        assert!(!wrong.correct);
+        assert_eq!(wrong.answer, "Nairobi");
+        assert_eq!(progress.streak, 0);
+        assert_eq!(progress.best_streak, 6);
+
+        // A new day resets the cap
+        progress.pending = pending(0).pending;
+        assert_eq!(grade(&mut progress, 0, 2 * day).unwrap().strix, STRIX_PER_CORRECT_ANSWER);
+        assert!(grade(&mut progress, 0, 2 * day).is_none());
+    }
+
+    #[test]
+    fn session_quiz_covers_each_featured_country_once() {
+        let session = Session {
+            id: "session-1".to_string(),
+            date: 0,
+            featured_countries: vec!["Kenya".to_string(), "Japan".to_string(), "Brazil".to_string()],
+            pairings: Vec::new(),
+        };
+        let mut progress = QuizProgress::default();
+        let mut rng = SeededRng::new(2);
+
+        let mut asked = Vec::new();
+        while let Some(question) = next_session_question(&mut progress, &session, countries(), &mut rng) {
+            asked.push(question.country.clone());
+            let answer = question.answer as usize;
+            progress.pending = Some(question);
+            grade(&mut progress, answer, 0);
+        }
+
+        assert_eq!(asked, session.featured_countries);
+        assert_eq!(progress.session_correct, 3);
+    }
+
+    #[test]
+    fn answers_can_be_letters_numbers_or_text() {
+        let question = pending(0).pending.unwrap();
+        assert_eq!(parse_answer("b", &question), Some(1));
+        assert_eq!(parse_answer("4", &question), Some(3));
+        assert_eq!(parse_answer("dodoma", &question), Some(2));
+        assert_eq!(parse_answer("5", &question), None);
+        assert_eq!(parse_answer("Paris", &question), None);
+    }
+
+    #[test]
+    fn questions_are_worded_in_the_reader_locale() {
+        let question = pending(0).pending.unwrap();
+        assert!(format_question(&question, "en").contains("What is the capital of Kenya 🇰🇪?"));
+        assert!(format_question(&question, "sw").contains("Mji mkuu wa Kenya 🇰🇪 ni upi?\n\n**A.** Nairobi"));
+    }
+}
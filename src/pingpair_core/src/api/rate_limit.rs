@@ -0,0 +1,291 @@
+use crate::api::handlers::{subcommand_label, SUBCOMMANDS};
+use crate::config::{RateLimit, RateLimitConfig};
+use crate::model::state;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+const NANOS_PER_MINUTE: f64 = 60.0 * NANOS_PER_SEC as f64;
+
+// Past this many buckets, full ones are dropped: a full bucket behaves the
+// same as one that was never created. If that isn't enough, the least
+// recently used go too, down to EVICT_TO so this doesn't run on every command.
+const MAX_BUCKETS: usize = 10_000;
+const EVICT_TO: usize = MAX_BUCKETS * 9 / 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Scope {
+    User,
+    Community,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: u64,
+}
+
+impl Bucket {
+    fn tokens_at(&self, limit: RateLimit, now: u64) -> f64 {
+        let refilled = now.saturating_sub(self.updated) as f64 * limit.per_minute as f64 / NANOS_PER_MINUTE;
+        (self.tokens + refilled).min(limit.capacity as f64)
+    }
+
+    fn refill(&mut self, limit: RateLimit, now: u64) {
+        self.tokens = self.tokens_at(limit, now);
+        self.updated = now;
+    }
+
+    // Nanoseconds until the next token
+    fn wait(&self, limit: RateLimit) -> u64 {
+        if self.tokens >= 1.0 {
+            0
+        } else {
+            ((1.0 - self.tokens) * NANOS_PER_MINUTE / limit.per_minute as f64).ceil() as u64
+        }
+    }
+}
+
+fn invalid(name: &str, limit: RateLimit) -> Option<String> {
+    (limit.capacity == 0 || limit.per_minute == 0)
+        .then(|| format!("Rate limit {} needs a capacity and per_minute of at least 1", name))
+}
+
+// Token buckets per user and per chat or community, for each subcommand
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<(Scope, String, &'static str), Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Result<Self, String> {
+        let mut errors: Vec<String> = [("rate_limits.user", config.user), ("rate_limits.community", config.community)]
+            .into_iter()
+            .filter_map(|(name, limit)| invalid(name, limit))
+            .collect();
+
+        let mut subcommands: Vec<&String> = config.subcommands.keys().collect();
+        subcommands.sort();
+        for subcommand in subcommands {
+            // "help" is an empty subcommand and "other" any unknown one
+            let known = SUBCOMMANDS.contains(&subcommand.as_str()) || subcommand == "help" || subcommand == "other";
+            if !known {
+                errors.push(format!(
+                    "Unknown subcommand in rate_limits.subcommands: \"{}\" (expected one of {}, help, other)",
+                    subcommand,
+                    SUBCOMMANDS.join(", ")
+                ));
+            }
+            let limits = &config.subcommands[subcommand];
+            for (scope, limit) in [("user", limits.user), ("community", limits.community)] {
+                let name = format!("rate_limits.subcommands.{}.{}", subcommand, scope);
+                errors.extend(limit.and_then(|limit| invalid(&name, limit)));
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors.join("\n"));
+        }
+
+        Ok(RateLimiter {
+            config: config.clone(),
+            buckets: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn limit(&self, scope: Scope, subcommand: &str) -> RateLimit {
+        let limits = self.config.subcommands.get(subcommand);
+        match scope {
+            Scope::User => limits.and_then(|limits| limits.user).unwrap_or(self.config.user),
+            Scope::Community => limits.and_then(|limits| limits.community).unwrap_or(self.config.community),
+        }
+    }
+
+    // Take a token for `subcommand` from both the user's and the community's
+    // bucket. When either is empty nothing is taken, and the error is the
+    // number of seconds until the command would be allowed.
+    pub fn check(&self, user: &str, community: &str, subcommand: &str) -> Result<(), u64> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let now = state::time();
+        let subcommand = subcommand_label(subcommand);
+        let keys = [
+            (Scope::User, user.to_string(), subcommand),
+            (Scope::Community, community.to_string(), subcommand),
+        ];
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_BUCKETS {
+            self.evict(&mut buckets, now);
+        }
+
+        let mut wait = 0;
+        for key in &keys {
+            let limit = self.limit(key.0, subcommand);
+            let bucket = buckets.entry(key.clone()).or_insert(Bucket {
+                tokens: limit.capacity as f64,
+                updated: now,
+            });
+            bucket.refill(limit, now);
+            wait = wait.max(bucket.wait(limit));
+        }
+
+        if wait > 0 {
+            return Err(wait.div_ceil(NANOS_PER_SEC));
+        }
+        for key in &keys {
+            if let Some(bucket) = buckets.get_mut(key) {
+                bucket.tokens -= 1.0;
+            }
+        }
+        Ok(())
+    }
+
+    fn evict(&self, buckets: &mut HashMap<(Scope, String, &'static str), Bucket>, now: u64) {
+        buckets.retain(|(scope, _, subcommand), bucket| {
+            let limit = self.limit(*scope, subcommand);
+            bucket.tokens_at(limit, now) < limit.capacity as f64
+        });
+        if buckets.len() <= EVICT_TO {
+            return;
+        }
+
+        let mut by_use: Vec<(u64, (Scope, String, &'static str))> = buckets
+            .iter()
+            .map(|(key, bucket)| (bucket.updated, key.clone()))
+            .collect();
+        by_use.sort_unstable_by_key(|(updated, _)| *updated);
+        let excess = buckets.len() - EVICT_TO;
+        for (_, key) in by_use.into_iter().take(excess) {
+            buckets.remove(&key);
+        }
+    }
+}
+
+static LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+
+// Install the limits from config; call once at startup
+pub fn configure(config: &RateLimitConfig) -> Result<(), String> {
+    let limiter = RateLimiter::new(config)?;
+    LIMITER
+        .set(limiter)
+        .map_err(|_| "Rate limits already configured".to_string())
+}
+
+pub fn limiter() -> &'static RateLimiter {
+    LIMITER.get_or_init(|| RateLimiter::new(&RateLimitConfig::default()).expect("default rate limits"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SubcommandRateLimits;
+    use crate::model::clock::FakeClock;
+    use crate::model::state::testing;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    // 2026-10-19T09:00:00Z
+    const START: u64 = 1_792_400_400 * 1_000_000_000;
+
+    fn config(user: (u32, u32), community: (u32, u32)) -> RateLimitConfig {
+        RateLimitConfig {
+            enabled: true,
+            user: RateLimit { capacity: user.0, per_minute: user.1 },
+            community: RateLimit { capacity: community.0, per_minute: community.1 },
+            subcommands: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn buckets_refill_with_the_clock() {
+        let clock = Arc::new(FakeClock::new(START));
+        let _guard = testing::fresh_state(clock.clone(), 1);
+        let limiter = RateLimiter::new(&config((2, 1), (100, 100))).unwrap();
+
+        assert_eq!(limiter.check("amina", "global", "quiz"), Ok(()));
+        assert_eq!(limiter.check("amina", "global", "quiz"), Ok(()));
+        assert_eq!(limiter.check("amina", "global", "quiz"), Err(60));
+
+        clock.advance(Duration::from_secs(45));
+        assert_eq!(limiter.check("amina", "global", "quiz"), Err(15));
+        clock.advance(Duration::from_secs(15));
+        assert_eq!(limiter.check("amina", "global", "quiz"), Ok(()));
+        assert_eq!(limiter.check("amina", "global", "quiz"), Err(60));
+
+        // A long pause refills up to the capacity only
+        clock.advance(Duration::from_secs(3600));
+        assert_eq!(limiter.check("amina", "global", "quiz"), Ok(()));
+        assert_eq!(limiter.check("amina", "global", "quiz"), Ok(()));
+        assert!(limiter.check("amina", "global", "quiz").is_err());
+    }
+
+    #[test]
+    fn users_subcommands_and_communities_have_their_own_buckets() {
+        let _guard = testing::fresh_state(Arc::new(FakeClock::new(START)), 1);
+        let limiter = RateLimiter::new(&config((1, 1), (2, 1))).unwrap();
+
+        assert_eq!(limiter.check("amina", "kenya-club", "quiz"), Ok(()));
+        assert!(limiter.check("amina", "kenya-club", "quiz").is_err());
+        assert_eq!(limiter.check("amina", "kenya-club", "profile"), Ok(()));
+        assert_eq!(limiter.check("kenji", "kenya-club", "quiz"), Ok(()));
+
+        // The community's quiz bucket is now empty for everyone in it, but a
+        // refused command does not use up the user's own token
+        assert!(limiter.check("lucia", "kenya-club", "quiz").is_err());
+        assert_eq!(limiter.check("lucia", "japan-club", "quiz"), Ok(()));
+    }
+
+    #[test]
+    fn subcommands_can_be_limited_separately() {
+        let _guard = testing::fresh_state(Arc::new(FakeClock::new(START)), 1);
+        let mut config = config((5, 5), (100, 100));
+        config.subcommands.insert("profile".to_string(), SubcommandRateLimits {
+            user: Some(RateLimit { capacity: 1, per_minute: 2 }),
+            community: None,
+        });
+        let limiter = RateLimiter::new(&config).unwrap();
+
+        assert_eq!(limiter.check("amina", "global", "PROFILE"), Ok(()));
+        assert_eq!(limiter.check("amina", "global", "profile"), Err(30));
+        assert_eq!(limiter.check("amina", "global", "stats"), Ok(()));
+
+        config.enabled = false;
+        let disabled = RateLimiter::new(&config).unwrap();
+        for _ in 0..10 {
+            assert_eq!(disabled.check("amina", "global", "profile"), Ok(()));
+        }
+    }
+
+    #[test]
+    fn a_flood_of_drained_buckets_stays_bounded() {
+        let clock = Arc::new(FakeClock::new(START));
+        let _guard = testing::fresh_state(clock.clone(), 1);
+        let limiter = RateLimiter::new(&config((1, 1), (u32::MAX, u32::MAX))).unwrap();
+
+        for user in 0..2 * MAX_BUCKETS {
+            clock.advance(Duration::from_millis(1));
+            assert_eq!(limiter.check(&format!("user-{}", user), "global", "quiz"), Ok(()));
+            assert!(limiter.buckets.lock().unwrap().len() <= MAX_BUCKETS);
+        }
+
+        // The most recent users keep their empty buckets
+        assert!(limiter.check(&format!("user-{}", 2 * MAX_BUCKETS - 1), "global", "quiz").is_err());
+    }
+
+    #[test]
+    fn invalid_limits_are_reported_together() {
+        let mut config = config((0, 1), (10, 10));
+        config.subcommands.insert("dance".to_string(), SubcommandRateLimits::default());
+        config.subcommands.insert("quiz".to_string(), SubcommandRateLimits {
+            user: None,
+            community: Some(RateLimit { capacity: 10, per_minute: 0 }),
+        });
+
+        let error = RateLimiter::new(&config).err().unwrap();
+        assert!(error.contains("rate_limits.user needs"));
+        assert!(error.contains("\"dance\""));
+        assert!(error.contains("rate_limits.subcommands.quiz.community needs"));
+        assert_eq!(error.lines().count(), 3);
+    }
+}
@@ -0,0 +1,261 @@
+use crate::config::TemplateOverrides;
+use crate::model::i18n::{self, DEFAULT_LOCALE};
+use crate::model::spotlight::DEFAULT_COMMUNITY;
+use std::collections::BTreeSet;
+use std::sync::OnceLock;
+
+// OpenChat rejects text messages longer than this
+pub const OPENCHAT_MAX_MESSAGE_LENGTH: usize = 10_000;
+
+const ELLIPSIS: char = '…';
+
+// Renders messages from named-variable templates: the locale's message
+// catalogue, overridden per community from config. Overrides for the
+// "global" community apply to every community that has none of its own.
+pub struct Renderer {
+    overrides: TemplateOverrides,
+    limit: usize,
+}
+
+impl Renderer {
+    // Every override must replace a known message in a shipped locale and
+    // may only use that message's placeholders. All problems are reported
+    // together.
+    pub fn new(overrides: &TemplateOverrides, limit: usize) -> Result<Self, String> {
+        let messages = i18n::messages();
+        let mut errors = Vec::new();
+
+        for (community, locales) in overrides {
+            for (locale, templates) in locales {
+                if !messages.locales().any(|shipped| shipped.code == *locale) {
+                    errors.push(format!("templates.{}.{}: unknown locale \"{}\"", community, locale, locale));
+                    continue;
+                }
+                for (key, template) in templates {
+                    let Some(default) = messages.template(DEFAULT_LOCALE, key) else {
+                        errors.push(format!("templates.{}.{}: unknown message \"{}\"", community, locale, key));
+                        continue;
+                    };
+                    let allowed = i18n::placeholders(default);
+                    let unknown: BTreeSet<&str> = i18n::placeholders(template).difference(&allowed).copied().collect();
+                    if !unknown.is_empty() {
+                        errors.push(format!(
+                            "templates.{}.{}.\"{}\": unknown placeholders {{{}}} (available: {{{}}})",
+                            community,
+                            locale,
+                            key,
+                            unknown.into_iter().collect::<Vec<&str>>().join("}, {"),
+                            allowed.into_iter().collect::<Vec<&str>>().join("}, {")
+                        ));
+                    }
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors.join("\n"));
+        }
+
+        Ok(Renderer {
+            overrides: overrides.clone(),
+            limit,
+        })
+    }
+
+    fn override_for(&self, community: &str, locale: &str, key: &str) -> Option<&str> {
+        self.overrides
+            .get(community)?
+            .get(locale)?
+            .get(key)
+            .map(String::as_str)
+    }
+
+    pub fn template(&self, community: &str, locale: &str, key: &str) -> Option<&str> {
+        let messages = i18n::messages();
+        let code = messages.find(locale).map(|locale| locale.code.as_str()).unwrap_or(DEFAULT_LOCALE);
+
+        self.override_for(community, code, key)
+            .or_else(|| self.override_for(DEFAULT_COMMUNITY, code, key))
+            .or_else(|| messages.template(locale, key))
+    }
+
+    // Missing keys render as the key itself, like `i18n::t`
+    pub fn render(&self, community: &str, locale: &str, key: &str, vars: &[(&str, String)]) -> String {
+        match self.template(community, locale, key) {
+            Some(template) => fit(template, vars, self.limit),
+            None => key.to_string(),
+        }
+    }
+}
+
+// Shorten a value to at most `max` characters, ending in "…". A multi-line
+// value such as a bullet list loses whole lines rather than half of one.
+fn shorten(value: &str, max: usize) -> String {
+    if value.chars().count() <= max {
+        return value.to_string();
+    }
+
+    let kept: String = value.chars().take(max.saturating_sub(1)).collect();
+    match kept.rfind('\n') {
+        Some(end) if end > 0 => format!("{}\n{}", &kept[..end], ELLIPSIS),
+        _ => format!("{}{}", kept, ELLIPSIS),
+    }
+}
+
+// Render within `limit` characters. The template's own text is kept and the
+// longest value is shortened, repeatedly, until the message fits. Only if
+// the template alone is too long is the message itself cut.
+pub fn fit(template: &str, vars: &[(&str, String)], limit: usize) -> String {
+    let mut vars = vars.to_vec();
+    let mut rendered = i18n::render(template, &vars);
+
+    loop {
+        let length = rendered.chars().count();
+        if length <= limit {
+            return rendered;
+        }
+
+        let longest = vars
+            .iter_mut()
+            .map(|(_, value)| value)
+            .filter(|value| value.chars().count() > 1)
+            .max_by_key(|value| value.chars().count());
+        let Some(longest) = longest else { break };
+
+        let max = longest.chars().count().saturating_sub(length - limit).max(1);
+        let shortened = shorten(longest, max);
+        if shortened == *longest {
+            break;
+        }
+        *longest = shortened;
+        rendered = i18n::render(template, &vars);
+    }
+
+    let mut cut: String = rendered.chars().take(limit.saturating_sub(1)).collect();
+    cut.push(ELLIPSIS);
+    cut
+}
+
+static RENDERER: OnceLock<Renderer> = OnceLock::new();
+
+// Install the template overrides from config; call once at startup
+pub fn configure(overrides: &TemplateOverrides) -> Result<(), String> {
+    let renderer = Renderer::new(overrides, OPENCHAT_MAX_MESSAGE_LENGTH)?;
+    RENDERER
+        .set(renderer)
+        .map_err(|_| "Templates already configured".to_string())
+}
+
+pub fn renderer() -> &'static Renderer {
+    RENDERER.get_or_init(|| {
+        Renderer::new(&TemplateOverrides::new(), OPENCHAT_MAX_MESSAGE_LENGTH).expect("no overrides are valid")
+    })
+}
+
+// A message for a community in the reader's locale
+pub fn render(community: &str, locale: &str, key: &str, vars: &[(&str, String)]) -> String {
+    renderer().render(community, locale, key, vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&'static str, &str)]) -> Vec<(&'static str, String)> {
+        pairs.iter().map(|(name, value)| (*name, value.to_string())).collect()
+    }
+
+    fn overrides(entries: &[(&str, &str, &str, &str)]) -> TemplateOverrides {
+        let mut overrides = TemplateOverrides::new();
+        for (community, locale, key, template) in entries {
+            overrides
+                .entry(community.to_string())
+                .or_default()
+                .entry(locale.to_string())
+                .or_default()
+                .insert(key.to_string(), template.to_string());
+        }
+        overrides
+    }
+
+    fn stock() -> Renderer {
+        Renderer::new(&TemplateOverrides::new(), OPENCHAT_MAX_MESSAGE_LENGTH).unwrap()
+    }
+
+    fn spotlight_vars() -> Vec<(&'static str, String)> {
+        vars(&[
+            ("country", "Kenya"),
+            ("flag", "🇰🇪"),
+            ("facts", "- Kenya is home to the Great Rift Valley\n- Nairobi has a national park inside the city\n- Kenyan runners hold many world records"),
+        ])
+    }
+
+    fn assert_golden(rendered: &str, golden: &str) {
+        assert_eq!(rendered.trim_end(), golden.trim_end());
+    }
+
+    #[test]
+    fn help_and_welcome_match_golden() {
+        assert_golden(&stock().render("global", "en", "help.menu", &[]), include_str!("testdata/help_en.golden"));
+        assert_golden(&stock().render("global", "es", "welcome.message", &[]), include_str!("testdata/welcome_es.golden"));
+    }
+
+    #[test]
+    fn spotlight_and_notification_match_golden() {
+        assert_golden(&stock().render("global", "en", "ping.spotlight", &spotlight_vars()), include_str!("testdata/spotlight_en.golden"));
+
+        let notification = stock().render("global", "fr", "notification.paired", &vars(&[
+            ("country", "Kenya"),
+            ("details", "\n**Pour briser la glace :**\n- Quel est ton plat préféré ?\n"),
+            ("url", "https://meet.jit.si/pingpair-abc"),
+            ("hours", "96"),
+        ]));
+        assert_golden(&notification, include_str!("testdata/notification_fr.golden"));
+    }
+
+    #[test]
+    fn long_values_are_shortened_to_the_limit() {
+        let renderer = Renderer {
+            overrides: TemplateOverrides::new(),
+            limit: 300,
+        };
+        let rendered = renderer.render("global", "en", "ping.spotlight", &spotlight_vars());
+        assert!(rendered.chars().count() <= 300);
+        assert_golden(&rendered, include_str!("testdata/spotlight_truncated.golden"));
+
+        // Values are cut before the template's own text
+        let rendered = fit("Name: {name}!", &vars(&[("name", "abcdefghij")]), 10);
+        assert_eq!(rendered, "Name: ab…!");
+
+        // A template longer than the limit is cut itself
+        let rendered = fit("A long template", &[], 6);
+        assert_eq!(rendered, "A lon…");
+    }
+
+    #[test]
+    fn community_overrides_fall_back_to_global_then_the_catalogue() {
+        let renderer = Renderer::new(&overrides(&[
+            ("global", "en", "help.menu", "Global help"),
+            ("c1", "en", "help.menu", "Help for c1"),
+            ("c1", "es", "welcome.message", "¡Hola, c1!"),
+        ]), OPENCHAT_MAX_MESSAGE_LENGTH).unwrap();
+
+        assert_eq!(renderer.render("c1", "en", "help.menu", &[]), "Help for c1");
+        assert_eq!(renderer.render("c2", "en", "help.menu", &[]), "Global help");
+        assert_eq!(renderer.render("c1", "es-MX", "welcome.message", &[]), "¡Hola, c1!");
+        assert!(renderer.render("c1", "es", "help.menu", &[]).starts_with("# Menú de ayuda de PingPair"));
+    }
+
+    #[test]
+    fn invalid_overrides_are_reported_together() {
+        let err = Renderer::new(&overrides(&[
+            ("c1", "en", "help.menuu", "Help"),
+            ("c1", "xx", "help.menu", "Help"),
+            ("c1", "en", "pair.partner", "Hi {name}, meet {partner}"),
+        ]), OPENCHAT_MAX_MESSAGE_LENGTH).err().unwrap();
+
+        assert!(err.contains("templates.c1.en: unknown message \"help.menuu\""));
+        assert!(err.contains("templates.c1.xx: unknown locale \"xx\""));
+        assert!(err.contains("templates.c1.en.\"pair.partner\": unknown placeholders {partner} (available: {name})"));
+    }
+}
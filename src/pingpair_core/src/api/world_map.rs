@@ -0,0 +1,318 @@
+use crate::api::render::OPENCHAT_MAX_MESSAGE_LENGTH;
+use crate::model::i18n::t;
+use crate::model::types::{Country, UserProfile};
+use std::collections::{BTreeMap, HashMap};
+
+// Continents in display order, with the globe that shows them best
+const CONTINENTS: [(&str, &str); 6] = [
+    ("Africa", "🌍"),
+    ("Asia", "🌏"),
+    ("Europe", "🌍"),
+    ("North America", "🌎"),
+    ("South America", "🌎"),
+    ("Oceania", "🌏"),
+];
+
+// When a map is too long, each continent is cut down to this many countries
+// in turn until it fits.
+const TRUNCATION_STEPS: [usize; 5] = [20, 10, 5, 3, 1];
+
+fn continent_order(continent: &str) -> (usize, String) {
+    let index = CONTINENTS
+        .iter()
+        .position(|(name, _)| *name == continent)
+        .unwrap_or(CONTINENTS.len());
+    (index, continent.to_string())
+}
+
+fn continent_icon(continent: &str) -> &'static str {
+    CONTINENTS
+        .iter()
+        .find(|(name, _)| *name == continent)
+        .map(|(_, icon)| *icon)
+        .unwrap_or("🏳️")
+}
+
+// Join entries with a middle dot, collapsing anything past `max` into "+N more"
+fn join_entries(entries: &[String], max: Option<usize>, locale: &str) -> String {
+    match max {
+        Some(max) if entries.len() > max => format!(
+            "{} · {}",
+            entries[..max].join(" · "),
+            t(locale, "map.more", &[("count", (entries.len() - max).to_string())])
+        ),
+        _ => entries.join(" · "),
+    }
+}
+
+// Render sections with progressively fewer entries per continent until the
+// message fits within `limit` characters.
+fn fit_to_limit(limit: usize, render: impl Fn(Option<usize>) -> String) -> String {
+    let mut rendered = render(None);
+    for max in TRUNCATION_STEPS {
+        if rendered.chars().count() <= limit {
+            return rendered;
+        }
+        rendered = render(Some(max));
+    }
+
+    if rendered.chars().count() > limit {
+        rendered = rendered.chars().take(limit.saturating_sub(1)).collect();
+        rendered.push('…');
+    }
+    rendered
+}
+
+// Community map: members per country, grouped by continent
+pub fn render_world_map(countries: &[Country], user_counts: &HashMap<String, u32>, locale: &str) -> String {
+    render_world_map_with_limit(countries, user_counts, locale, OPENCHAT_MAX_MESSAGE_LENGTH)
+}
+
+pub fn render_world_map_with_limit(
+    countries: &[Country],
+    user_counts: &HashMap<String, u32>,
+    locale: &str,
+    limit: usize,
+) -> String {
+    // continent -> [(country, members)]
+    let mut continents: BTreeMap<(usize, String), Vec<(&Country, u32)>> = BTreeMap::new();
+    for country in countries {
+        let members = user_counts.get(&country.name).copied().unwrap_or(0);
+        if members > 0 {
+            continents
+                .entry(continent_order(&country.continent))
+                .or_default()
+                .push((country, members));
+        }
+    }
+
+    let header = t(locale, "map.world_title", &[]);
+    if continents.is_empty() {
+        return format!("{}\n\n{}", header, t(locale, "map.world_empty", &[]));
+    }
+
+    for entries in continents.values_mut() {
+        entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.name.cmp(&b.0.name)));
+    }
+
+    let members: u32 = continents.values().flatten().map(|(_, members)| members).sum();
+    let country_count = continents.values().map(Vec::len).sum::<usize>();
+
+    fit_to_limit(limit, |max| {
+        let sections = continents
+            .iter()
+            .map(|((_, continent), entries)| {
+                let total: u32 = entries.iter().map(|(_, members)| members).sum();
+                let entries = entries
+                    .iter()
+                    .map(|(country, members)| format!("{} {} {}", country.flag, country.name, members))
+                    .collect::<Vec<String>>();
+                format!(
+                    "{} **{}** ({})\n{}",
+                    continent_icon(continent),
+                    continent,
+                    total,
+                    join_entries(&entries, max, locale)
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n\n");
+
+        let summary = t(locale, "map.world_summary", &[
+            ("members", members.to_string()),
+            ("countries", country_count.to_string()),
+        ]);
+        format!("{}\n\n{}\n\n{}", header, summary, sections)
+    })
+}
+
+// Personal map: the countries a user has "visited" through pairings, with
+// their home country marked.
+pub fn render_personal_map(user: &UserProfile, countries: &[Country]) -> String {
+    render_personal_map_with_limit(user, countries, OPENCHAT_MAX_MESSAGE_LENGTH)
+}
+
+// In the user's own locale
+pub fn render_personal_map_with_limit(user: &UserProfile, countries: &[Country], limit: usize) -> String {
+    let locale = user.locale.as_str();
+    let visited = |country: &Country| {
+        user.countries_visited
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(&country.name))
+    };
+    let is_home = |country: &Country| user.country.eq_ignore_ascii_case(&country.name);
+
+    // continent -> (countries in catalogue, highlighted countries)
+    let mut continents: BTreeMap<(usize, String), (usize, Vec<&Country>)> = BTreeMap::new();
+    for country in countries {
+        let entry = continents.entry(continent_order(&country.continent)).or_default();
+        entry.0 += 1;
+        if visited(country) || is_home(country) {
+            entry.1.push(country);
+        }
+    }
+
+    let visited_count = countries.iter().filter(|country| visited(*country)).count();
+    let header = format!(
+        "{}\n\n{}",
+        t(locale, "map.personal_title", &[("name", user.name.clone())]),
+        t(locale, "map.personal_summary", &[
+            ("visited", visited_count.to_string()),
+            ("total", countries.len().to_string()),
+        ])
+    );
+
+    let unexplored = continents
+        .iter()
+        .filter(|((_, continent), (_, highlighted))| {
+            !highlighted.iter().any(|country| visited(*country))
+                && CONTINENTS.iter().any(|(name, _)| *name == continent.as_str())
+        })
+        .map(|((_, continent), _)| format!("{} {}", continent_icon(continent), continent))
+        .collect::<Vec<String>>();
+    let footer = if unexplored.is_empty() {
+        String::new()
+    } else {
+        format!("\n\n{}", t(locale, "map.still_to_explore", &[("continents", unexplored.join(", "))]))
+    };
+
+    if visited_count == 0 {
+        return format!("{}\n\n{}{}", header, t(locale, "map.personal_empty", &[]), footer);
+    }
+
+    fit_to_limit(limit, |max| {
+        let sections = continents
+            .iter()
+            .filter(|(_, (_, highlighted))| !highlighted.is_empty())
+            .map(|((_, continent), (total, highlighted))| {
+                let mut highlighted = highlighted.clone();
+                highlighted.sort_by(|a, b| is_home(*b).cmp(&is_home(*a)).then_with(|| a.name.cmp(&b.name)));
+
+                let visits = highlighted.iter().filter(|country| visited(**country)).count();
+                let entries = highlighted
+                    .iter()
+                    .map(|&country| {
+                        if is_home(country) && !visited(country) {
+                            format!("🏠 {} {}", country.flag, country.name)
+                        } else if is_home(country) {
+                            format!("🏠 **{} {}**", country.flag, country.name)
+                        } else {
+                            format!("**{} {}**", country.flag, country.name)
+                        }
+                    })
+                    .collect::<Vec<String>>();
+                format!(
+                    "{} **{}** {}/{}\n{}",
+                    continent_icon(continent),
+                    continent,
+                    visits,
+                    total,
+                    join_entries(&entries, max, locale)
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n\n");
+
+        format!("{}\n\n{}{}", header, sections, footer)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::Principal;
+
+    fn country(name: &str, continent: &str, flag: &str) -> Country {
+        Country {
+            name: name.to_string(),
+            iso2: String::new(),
+            iso3: String::new(),
+            aliases: Vec::new(),
+            native_names: Vec::new(),
+            fun_facts: Vec::new(),
+            continent: continent.to_string(),
+            flag: flag.to_string(),
+            traditions: Vec::new(),
+            languages: Vec::new(),
+            available: true,
+            population: 0,
+            capital: String::new(),
+            currency: String::new(),
+        }
+    }
+
+    fn catalogue() -> Vec<Country> {
+        vec![
+            country("Kenya", "Africa", "🇰🇪"),
+            country("Nigeria", "Africa", "🇳🇬"),
+            country("India", "Asia", "🇮🇳"),
+            country("Japan", "Asia", "🇯🇵"),
+            country("Germany", "Europe", "🇩🇪"),
+            country("Brazil", "South America", "🇧🇷"),
+        ]
+    }
+
+    fn user(country: &str, visited: &[&str]) -> UserProfile {
+        UserProfile {
+            user_id: Principal::anonymous(),
+            name: "Amina".to_string(),
+            country: country.to_string(),
+            interests: Vec::new(),
+            bio: String::new(),
+            net_worth: 5,
+            badges: Vec::new(),
+            countries_visited: visited.iter().map(|c| c.to_string()).collect(),
+            timezone: "UTC".to_string(),
+            languages: Vec::new(),
+            language_exchange: false,
+            join_date: 0,
+            active: true,
+            locale: "en".to_string(),
+        }
+    }
+
+    #[test]
+    fn world_map_matches_golden() {
+        let counts: HashMap<String, u32> = [("Kenya", 4), ("Nigeria", 1), ("India", 2), ("Japan", 2), ("Brazil", 3), ("Germany", 0)]
+            .into_iter()
+            .map(|(name, count)| (name.to_string(), count))
+            .collect();
+
+        let rendered = render_world_map(&catalogue(), &counts, "en");
+        assert_eq!(rendered.trim_end(), include_str!("testdata/world_map.golden").trim_end());
+    }
+
+    #[test]
+    fn personal_map_matches_golden() {
+        let rendered = render_personal_map(&user("Kenya", &["India", "Brazil", "Nigeria"]), &catalogue());
+        assert_eq!(rendered.trim_end(), include_str!("testdata/personal_map.golden").trim_end());
+    }
+
+    #[test]
+    fn empty_personal_map_matches_golden() {
+        let rendered = render_personal_map(&user("Kenya", &[]), &catalogue());
+        assert_eq!(rendered.trim_end(), include_str!("testdata/personal_map_empty.golden").trim_end());
+    }
+
+    #[test]
+    fn large_maps_are_truncated_to_the_limit() {
+        let countries: Vec<Country> = (0..300)
+            .map(|i| country(&format!("Country {}", i), "Africa", "🏳️"))
+            .collect();
+        let counts: HashMap<String, u32> = countries.iter().map(|c| (c.name.clone(), 1)).collect();
+
+        let rendered = render_world_map_with_limit(&countries, &counts, "en", 500);
+        assert!(rendered.chars().count() <= 500);
+        assert!(rendered.contains("more"));
+        assert!(rendered.contains("**300 members in 300 countries**"));
+    }
+
+    #[test]
+    fn maps_follow_the_user_locale() {
+        let mut user = user("Kenya", &["India"]);
+        user.locale = "es".to_string();
+
+        let rendered = render_personal_map(&user, &catalogue());
+        assert!(rendered.starts_with("# Mapa de PingPair de Amina 🧭\n\n**Has visitado 1 de 6 países**"));
+    }
+}
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// community -> locale -> message key -> template
+pub type TemplateOverrides = HashMap<String, HashMap<String, HashMap<String, String>>>;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct MeetingConfig {
+    pub provider: MeetingProviderKind,
+    // Jitsi instance, e.g. a self-hosted https://meet.example.org
+    pub base_url: String,
+    // Used by the "template" provider, e.g. https://video.example.com/{room}
+    pub url_template: Option<String>,
+    pub link_ttl_secs: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MeetingProviderKind {
+    Jitsi,
+    Template,
+}
+
+impl Default for MeetingConfig {
+    fn default() -> Self {
+        MeetingConfig {
+            provider: MeetingProviderKind::Jitsi,
+            base_url: "https://meet.jit.si".to_string(),
+            url_template: None,
+            // A session lasts 3-4 days
+            link_ttl_secs: 4 * 24 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct CalendarConfig {
+    // Public base URL of this server, used to build calendar download links
+    pub public_url: String,
+    // HMAC key for signing calendar links
+    pub secret: Option<String>,
+    pub link_ttl_secs: u64,
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        CalendarConfig {
+            public_url: "http://localhost:13457".to_string(),
+            secret: None,
+            link_ttl_secs: 7 * 24 * 60 * 60,
+        }
+    }
+}
+
+// Token bucket: up to `capacity` commands at once, refilled at `per_minute`
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimit {
+    pub capacity: u32,
+    pub per_minute: u32,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct SubcommandRateLimits {
+    pub user: Option<RateLimit>,
+    pub community: Option<RateLimit>,
+}
+
+// Buckets are kept per subcommand, so spamming one does not block the others
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    // Per user (the command's initiator)
+    pub user: RateLimit,
+    // Shared by everyone in a chat or community
+    pub community: RateLimit,
+    // Overrides by subcommand, e.g. a tighter limit for `quiz`
+    pub subcommands: HashMap<String, SubcommandRateLimits>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            enabled: true,
+            user: RateLimit { capacity: 10, per_minute: 6 },
+            community: RateLimit { capacity: 120, per_minute: 120 },
+            subcommands: HashMap::new(),
+        }
+    }
+}
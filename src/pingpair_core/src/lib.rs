@@ -0,0 +1,4 @@
+pub mod api;
+pub mod config;
+pub mod metrics;
+pub mod model;
@@ -0,0 +1,341 @@
+use crate::model::state;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+// Counters, gauges and histograms exported on the bot server's /metrics in
+// the Prometheus text format. Dashboards and alerts refer to these names and
+// labels, so treat them as stable: add new metrics rather than renaming.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+pub struct Metric {
+    pub name: &'static str,
+    pub kind: Kind,
+    pub help: &'static str,
+}
+
+// Commands by `subcommand` (see `handlers::subcommand_label`) and `response`:
+// success, bad_request, internal_error or too_many_requests
+pub static COMMANDS: Metric = Metric {
+    name: "pingpair_commands_total",
+    kind: Kind::Counter,
+    help: "Commands handled, by subcommand and response",
+};
+
+// Time from receiving a command to its response, by `subcommand` and
+// `response` as for COMMANDS
+pub static COMMAND_DURATION: Metric = Metric {
+    name: "pingpair_command_duration_seconds",
+    kind: Kind::Histogram,
+    help: "Command latency in seconds, by subcommand and response",
+};
+
+// Command JWTs by the OpenChat public `key` whose signature they carried, or
+// "none" when no key accepted the token
+pub static OC_KEY_VERIFICATIONS: Metric = Metric {
+    name: "pingpair_oc_key_verifications_total",
+    kind: Kind::Counter,
+    help: "Command tokens verified, by the OpenChat public key that accepted them",
+};
+
+pub static USERS: Metric = Metric {
+    name: "pingpair_users",
+    kind: Kind::Gauge,
+    help: "Registered users",
+};
+
+// Users who have not paused pairing with `/pingpair skip`
+pub static ACTIVE_USERS: Metric = Metric {
+    name: "pingpair_active_users",
+    kind: Kind::Gauge,
+    help: "Users available for pairing",
+};
+
+// Users who picked a featured country in the current session
+pub static SESSION_OPT_INS: Metric = Metric {
+    name: "pingpair_session_opt_ins",
+    kind: Kind::Gauge,
+    help: "Opt-ins to the current session",
+};
+
+// Pairings by `event`: created, completed or cancelled
+pub static PAIRINGS: Metric = Metric {
+    name: "pingpair_pairings_total",
+    kind: Kind::Counter,
+    help: "Pairing lifecycle events",
+};
+
+pub static MATCHER_DURATION: Metric = Metric {
+    name: "pingpair_matcher_duration_seconds",
+    kind: Kind::Histogram,
+    help: "Matcher run time in seconds",
+};
+
+// Candidates the last matcher run could not find a partner for
+pub static MATCHER_UNMATCHED: Metric = Metric {
+    name: "pingpair_matcher_unmatched_users",
+    kind: Kind::Gauge,
+    help: "Users left unmatched by the last matcher run",
+};
+
+// Snapshot encode/decode time by `operation`: save or load
+pub static SNAPSHOT_DURATION: Metric = Metric {
+    name: "pingpair_snapshot_duration_seconds",
+    kind: Kind::Histogram,
+    help: "State snapshot time in seconds, by operation",
+};
+
+pub static SNAPSHOT_BYTES: Metric = Metric {
+    name: "pingpair_snapshot_bytes",
+    kind: Kind::Gauge,
+    help: "Size of the last state snapshot saved",
+};
+
+// Every metric, in the order they are exported
+static ALL: [&Metric; 11] = [
+    &COMMANDS,
+    &COMMAND_DURATION,
+    &OC_KEY_VERIFICATIONS,
+    &USERS,
+    &ACTIVE_USERS,
+    &SESSION_OPT_INS,
+    &PAIRINGS,
+    &MATCHER_DURATION,
+    &MATCHER_UNMATCHED,
+    &SNAPSHOT_DURATION,
+    &SNAPSHOT_BYTES,
+];
+
+// Upper bounds, in seconds, of the histogram buckets
+const BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+type Labels = Vec<(&'static str, String)>;
+
+enum Value {
+    Scalar(f64),
+    Histogram { counts: [u64; BUCKETS.len()], sum: f64, count: u64 },
+}
+
+struct Registry {
+    series: Mutex<BTreeMap<(&'static str, Labels), Value>>,
+}
+
+static REGISTRY: Registry = Registry::new();
+
+impl Registry {
+    const fn new() -> Self {
+        Registry { series: Mutex::new(BTreeMap::new()) }
+    }
+
+    fn record(&self, metric: &Metric, labels: &[(&'static str, &str)], value: f64) {
+        let labels = labels.iter().map(|&(name, value)| (name, value.to_string())).collect();
+        let mut series = self.series.lock().unwrap();
+        let entry = series.entry((metric.name, labels)).or_insert_with(|| match metric.kind {
+            Kind::Histogram => Value::Histogram { counts: [0; BUCKETS.len()], sum: 0.0, count: 0 },
+            _ => Value::Scalar(0.0),
+        });
+
+        match (metric.kind, entry) {
+            (Kind::Counter, Value::Scalar(total)) => *total += value,
+            (Kind::Gauge, Value::Scalar(current)) => *current = value,
+            (Kind::Histogram, Value::Histogram { counts, sum, count }) => {
+                for (bound, bucket) in BUCKETS.iter().zip(counts.iter_mut()) {
+                    if value <= *bound {
+                        *bucket += 1;
+                    }
+                }
+                *sum += value;
+                *count += 1;
+            }
+            _ => unreachable!("a metric's kind never changes"),
+        }
+    }
+
+    fn render(&self, metrics: &[&Metric]) -> String {
+        let series = self.series.lock().unwrap();
+        let mut out = String::new();
+
+        for metric in metrics {
+            let kind = match metric.kind {
+                Kind::Counter => "counter",
+                Kind::Gauge => "gauge",
+                Kind::Histogram => "histogram",
+            };
+            let _ = writeln!(out, "# HELP {} {}", metric.name, metric.help);
+            let _ = writeln!(out, "# TYPE {} {}", metric.name, kind);
+
+            for ((_, labels), value) in series.range((metric.name, Vec::new())..).take_while(|((name, _), _)| *name == metric.name) {
+                match value {
+                    Value::Scalar(value) => {
+                        let _ = writeln!(out, "{}{} {}", metric.name, format_labels(labels, None), value);
+                    }
+                    Value::Histogram { counts, sum, count } => {
+                        for (bound, bucket) in BUCKETS.iter().zip(counts) {
+                            let le = bound.to_string();
+                            let _ = writeln!(out, "{}_bucket{} {}", metric.name, format_labels(labels, Some(&le)), bucket);
+                        }
+                        let _ = writeln!(out, "{}_bucket{} {}", metric.name, format_labels(labels, Some("+Inf")), count);
+                        let _ = writeln!(out, "{}_sum{} {}", metric.name, format_labels(labels, None), sum);
+                        let _ = writeln!(out, "{}_count{} {}", metric.name, format_labels(labels, None), count);
+                    }
+                }
+            }
+        }
+
+        out
+    }
+}
+
+fn format_labels(labels: &Labels, le: Option<&str>) -> String {
+    let mut pairs: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape(value)))
+        .collect();
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{}\"", le));
+    }
+
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+impl Metric {
+    // Add one to a counter
+    pub fn inc(&self, labels: &[(&'static str, &str)]) {
+        debug_assert_eq!(self.kind, Kind::Counter, "{} is not a counter", self.name);
+        REGISTRY.record(self, labels, 1.0);
+    }
+
+    pub fn set(&self, labels: &[(&'static str, &str)], value: f64) {
+        debug_assert_eq!(self.kind, Kind::Gauge, "{} is not a gauge", self.name);
+        REGISTRY.record(self, labels, value);
+    }
+
+    pub fn observe(&self, labels: &[(&'static str, &str)], value: f64) {
+        debug_assert_eq!(self.kind, Kind::Histogram, "{} is not a histogram", self.name);
+        REGISTRY.record(self, labels, value);
+    }
+}
+
+// Measures a duration for a histogram. `state::time()` can't: it may be a
+// fake clock, and the canister's stands still for a whole message. The
+// canister has no monotonic clock either, so there it always reads 0; its
+// metrics are not exported.
+pub struct Timer {
+    #[cfg(not(target_arch = "wasm32"))]
+    start: Instant,
+}
+
+impl Timer {
+    pub fn start() -> Self {
+        Timer {
+            #[cfg(not(target_arch = "wasm32"))]
+            start: Instant::now(),
+        }
+    }
+
+    pub fn seconds(&self) -> f64 {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.start.elapsed().as_secs_f64();
+        #[cfg(target_arch = "wasm32")]
+        return 0.0;
+    }
+}
+
+// Every metric in the Prometheus text format, with the store gauges read
+// at scrape time
+pub fn gather() -> String {
+    let users = state::get_users();
+    USERS.set(&[], users.len() as f64);
+    ACTIVE_USERS.set(&[], users.iter().filter(|user| user.active).count() as f64);
+    SESSION_OPT_INS.set(&[], state::get_session_pick_count() as f64);
+
+    REGISTRY.render(&ALL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::clock::FakeClock;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    static REQUESTS: Metric = Metric { name: "test_requests_total", kind: Kind::Counter, help: "Requests" };
+    static QUEUE: Metric = Metric { name: "test_queue", kind: Kind::Gauge, help: "Queue length" };
+    static LATENCY: Metric = Metric { name: "test_latency_seconds", kind: Kind::Histogram, help: "Latency" };
+
+    #[test]
+    fn renders_the_prometheus_text_format() {
+        let registry = Registry::new();
+        registry.record(&REQUESTS, &[("subcommand", "start"), ("response", "success")], 1.0);
+        registry.record(&REQUESTS, &[("subcommand", "start"), ("response", "success")], 1.0);
+        registry.record(&REQUESTS, &[("subcommand", "say \"hi\""), ("response", "bad_request")], 1.0);
+        registry.record(&QUEUE, &[], 7.0);
+        registry.record(&QUEUE, &[], 3.0);
+
+        assert_eq!(
+            registry.render(&[&REQUESTS, &QUEUE]),
+            "# HELP test_requests_total Requests\n\
+             # TYPE test_requests_total counter\n\
+             test_requests_total{subcommand=\"say \\\"hi\\\"\",response=\"bad_request\"} 1\n\
+             test_requests_total{subcommand=\"start\",response=\"success\"} 2\n\
+             # HELP test_queue Queue length\n\
+             # TYPE test_queue gauge\n\
+             test_queue 3\n"
+        );
+    }
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let registry = Registry::new();
+        registry.record(&LATENCY, &[("subcommand", "pair")], 0.02);
+        registry.record(&LATENCY, &[("subcommand", "pair")], 0.3);
+        registry.record(&LATENCY, &[("subcommand", "pair")], 60.0);
+
+        let text = registry.render(&[&LATENCY]);
+        assert!(text.contains("# TYPE test_latency_seconds histogram\n"));
+        assert!(text.contains("test_latency_seconds_bucket{subcommand=\"pair\",le=\"0.01\"} 0\n"));
+        assert!(text.contains("test_latency_seconds_bucket{subcommand=\"pair\",le=\"0.025\"} 1\n"));
+        assert!(text.contains("test_latency_seconds_bucket{subcommand=\"pair\",le=\"0.5\"} 2\n"));
+        assert!(text.contains("test_latency_seconds_bucket{subcommand=\"pair\",le=\"10\"} 2\n"));
+        assert!(text.contains("test_latency_seconds_bucket{subcommand=\"pair\",le=\"+Inf\"} 3\n"));
+        assert!(text.contains("test_latency_seconds_sum{subcommand=\"pair\"} 60.32\n"));
+        assert!(text.contains("test_latency_seconds_count{subcommand=\"pair\"} 3\n"));
+    }
+
+    #[test]
+    fn exported_names_follow_prometheus_conventions() {
+        for metric in ALL {
+            assert!(metric.name.starts_with("pingpair_"), "{}", metric.name);
+            assert_eq!(metric.kind == Kind::Counter, metric.name.ends_with("_total"), "{}", metric.name);
+            if metric.kind == Kind::Histogram {
+                assert!(metric.name.ends_with("_seconds"), "{}", metric.name);
+            }
+        }
+    }
+
+    #[test]
+    fn timers_run_while_the_store_clock_stands_still() {
+        let _guard = state::testing::fresh_state(Arc::new(FakeClock::new(0)), 1);
+        let timer = Timer::start();
+        std::thread::sleep(Duration::from_millis(5));
+
+        assert_eq!(state::time(), 0);
+        assert!(timer.seconds() >= 0.005);
+    }
+}
@@ -0,0 +1,79 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::model::types::UserProfile;
+
+// Placeholder country of users who haven't set one yet
+pub const UNKNOWN_COUNTRY: &str = "Unknown";
+
+// country -> ids of active users living there. Kept up to date by the state
+// store on every profile change instead of being rebuilt from all users.
+#[derive(Clone, Debug, Default, PartialEq, CandidType, Serialize, Deserialize)]
+pub struct AvailabilityIndex {
+    countries: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl AvailabilityIndex {
+    // Full rebuild, used at startup and to check the incremental index
+    pub fn rebuild<'a>(users: impl IntoIterator<Item = &'a UserProfile>) -> Self {
+        let mut index = AvailabilityIndex::default();
+        for user in users {
+            index.insert(user);
+        }
+        index
+    }
+
+    fn counts(user: &UserProfile) -> bool {
+        user.active && user.country != UNKNOWN_COUNTRY && !user.country.is_empty()
+    }
+
+    fn insert(&mut self, user: &UserProfile) {
+        if Self::counts(user) {
+            self.countries
+                .entry(user.country.clone())
+                .or_default()
+                .insert(user.user_id.to_text());
+        }
+    }
+
+    fn remove(&mut self, user: &UserProfile) {
+        if let Some(users) = self.countries.get_mut(&user.country) {
+            users.remove(&user.user_id.to_text());
+            if users.is_empty() {
+                self.countries.remove(&user.country);
+            }
+        }
+    }
+
+    // Apply a change to one user: `None` before means created, `None` after
+    // means deleted.
+    pub fn update(&mut self, before: Option<&UserProfile>, after: Option<&UserProfile>) {
+        if let Some(before) = before {
+            self.remove(before);
+        }
+        if let Some(after) = after {
+            self.insert(after);
+        }
+    }
+
+    pub fn is_available(&self, country: &str) -> bool {
+        self.countries.contains_key(country)
+    }
+
+    // Countries with at least one active user, alphabetically
+    pub fn countries(&self) -> impl Iterator<Item = &String> {
+        self.countries.keys()
+    }
+
+    pub fn users_in(&self, country: &str) -> Vec<String> {
+        self.countries
+            .get(country)
+            .map(|users| users.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn user_count(&self, country: &str) -> usize {
+        self.countries.get(country).map(BTreeSet::len).unwrap_or(0)
+    }
+}
@@ -0,0 +1,371 @@
+use crate::model::types::Country;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::sync::OnceLock;
+use toml::Spanned;
+
+// Schema version of src/pingpair_core/data/countries.toml understood by this build
+pub const SUPPORTED_VERSION: u32 = 1;
+
+pub const CONTINENTS: [&str; 6] = [
+    "Africa",
+    "Asia",
+    "Europe",
+    "North America",
+    "South America",
+    "Oceania",
+];
+
+// The catalogue shipped with the binary
+const BUNDLED: &str = include_str!("../../data/countries.toml");
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CatalogueFile {
+    version: Spanned<u32>,
+    #[serde(rename = "country", default)]
+    countries: Vec<CountryRecord>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CountryRecord {
+    name: Spanned<String>,
+    iso2: Spanned<String>,
+    iso3: Spanned<String>,
+    #[serde(default)]
+    aliases: Vec<Spanned<String>>,
+    #[serde(default)]
+    native_names: Vec<Spanned<String>>,
+    continent: Spanned<String>,
+    capital: Spanned<String>,
+    currency: Spanned<String>,
+    languages: Spanned<Vec<String>>,
+    population: Spanned<u64>,
+    flag: Spanned<String>,
+    facts: Spanned<Vec<String>>,
+    traditions: Spanned<Vec<String>>,
+}
+
+// A problem found while loading, pointing at a line of the data file
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogueIssue {
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Debug)]
+pub struct CatalogueError {
+    pub source_name: String,
+    pub issues: Vec<CatalogueIssue>,
+}
+
+impl fmt::Display for CatalogueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines = self.issues
+            .iter()
+            .map(|issue| format!("{}:{}: {}", self.source_name, issue.line, issue.message))
+            .collect::<Vec<String>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl std::error::Error for CatalogueError {}
+
+pub(crate) fn line_of(source: &str, offset: usize) -> usize {
+    source[..offset.min(source.len())].matches('\n').count() + 1
+}
+
+// The flag emoji is the pair of regional indicator symbols for the ISO code
+pub fn flag_for(iso2: &str) -> String {
+    iso2.chars()
+        .filter_map(|c| char::from_u32(0x1F1E6 + (c as u32).wrapping_sub('A' as u32)))
+        .collect()
+}
+
+pub struct CountryCatalogue {
+    pub version: u32,
+    countries: Vec<Country>,
+    // lowercase name / ISO alpha-2 / ISO alpha-3 / alias -> index into `countries`
+    index: HashMap<String, usize>,
+}
+
+impl CountryCatalogue {
+    pub fn parse(source_name: &str, source: &str) -> Result<Self, CatalogueError> {
+        let error = |issues: Vec<CatalogueIssue>| CatalogueError {
+            source_name: source_name.to_string(),
+            issues,
+        };
+
+        let file: CatalogueFile = toml::from_str(source).map_err(|e| {
+            error(vec![CatalogueIssue {
+                line: e.span().map(|span| line_of(source, span.start)).unwrap_or(1),
+                message: e.message().to_string(),
+            }])
+        })?;
+
+        let mut issues = Vec::new();
+        let mut issue = |span: std::ops::Range<usize>, message: String| {
+            issues.push(CatalogueIssue {
+                line: line_of(source, span.start),
+                message,
+            });
+        };
+
+        if *file.version.get_ref() != SUPPORTED_VERSION {
+            issue(
+                file.version.span(),
+                format!("unsupported catalogue version {} (expected {})", file.version.get_ref(), SUPPORTED_VERSION),
+            );
+        }
+
+        let mut names = HashSet::new();
+        let mut iso2s = HashSet::new();
+        let mut iso3s = HashSet::new();
+
+        for record in &file.countries {
+            let name = record.name.get_ref();
+            let iso2 = record.iso2.get_ref();
+            let iso3 = record.iso3.get_ref();
+
+            if name.trim().is_empty() {
+                issue(record.name.span(), "name must not be empty".to_string());
+            } else if !names.insert(name.to_lowercase()) {
+                issue(record.name.span(), format!("duplicate country \"{}\"", name));
+            }
+
+            if iso2.len() != 2 || !iso2.chars().all(|c| c.is_ascii_uppercase()) {
+                issue(record.iso2.span(), format!("iso2 must be two uppercase letters, got \"{}\"", iso2));
+            } else if !iso2s.insert(iso2.clone()) {
+                issue(record.iso2.span(), format!("duplicate iso2 code \"{}\"", iso2));
+            } else if record.flag.get_ref() != &flag_for(iso2) {
+                issue(record.flag.span(), format!("flag does not match iso2 code \"{}\"", iso2));
+            }
+
+            if iso3.len() != 3 || !iso3.chars().all(|c| c.is_ascii_uppercase()) {
+                issue(record.iso3.span(), format!("iso3 must be three uppercase letters, got \"{}\"", iso3));
+            } else if !iso3s.insert(iso3.clone()) {
+                issue(record.iso3.span(), format!("duplicate iso3 code \"{}\"", iso3));
+            }
+
+            if !CONTINENTS.contains(&record.continent.get_ref().as_str()) {
+                issue(
+                    record.continent.span(),
+                    format!("unknown continent \"{}\" (expected one of {})", record.continent.get_ref(), CONTINENTS.join(", ")),
+                );
+            }
+
+            for (field, value) in [("capital", &record.capital), ("currency", &record.currency)] {
+                if value.get_ref().trim().is_empty() {
+                    issue(value.span(), format!("{} must not be empty", field));
+                }
+            }
+
+            for (field, values) in [("languages", &record.languages), ("facts", &record.facts), ("traditions", &record.traditions)] {
+                if values.get_ref().is_empty() || values.get_ref().iter().any(|v| v.trim().is_empty()) {
+                    issue(values.span(), format!("{} must be a non-empty list of non-empty strings", field));
+                }
+            }
+
+            if *record.population.get_ref() == 0 {
+                issue(record.population.span(), "population must be greater than zero".to_string());
+            }
+        }
+
+        // An alias may repeat its own country's name or code but must not
+        // point at another country.
+        let mut owners: HashMap<String, &str> = HashMap::new();
+        for record in &file.countries {
+            let name = record.name.get_ref().as_str();
+            for key in [record.name.get_ref(), record.iso2.get_ref(), record.iso3.get_ref()] {
+                owners.entry(key.to_lowercase()).or_insert(name);
+            }
+        }
+        for record in &file.countries {
+            let name = record.name.get_ref().as_str();
+            for alias in record.aliases.iter().chain(&record.native_names) {
+                let key = alias.get_ref().trim().to_lowercase();
+                if key.is_empty() {
+                    issue(alias.span(), format!("empty alias for \"{}\"", name));
+                    continue;
+                }
+                match owners.get(&key) {
+                    Some(owner) if *owner != name => issue(
+                        alias.span(),
+                        format!("alias \"{}\" of \"{}\" already refers to \"{}\"", alias.get_ref(), name, owner),
+                    ),
+                    Some(_) => {}
+                    None => {
+                        owners.insert(key, name);
+                    }
+                }
+            }
+        }
+
+        if file.countries.is_empty() {
+            issue(file.version.span(), "catalogue contains no countries".to_string());
+        }
+
+        if !issues.is_empty() {
+            return Err(error(issues));
+        }
+
+        let countries: Vec<Country> = file.countries
+            .into_iter()
+            .map(|record| Country {
+                name: record.name.into_inner(),
+                iso2: record.iso2.into_inner(),
+                iso3: record.iso3.into_inner(),
+                aliases: record.aliases.into_iter().map(Spanned::into_inner).collect(),
+                native_names: record.native_names.into_iter().map(Spanned::into_inner).collect(),
+                fun_facts: record.facts.into_inner(),
+                continent: record.continent.into_inner(),
+                flag: record.flag.into_inner(),
+                traditions: record.traditions.into_inner(),
+                languages: record.languages.into_inner(),
+                available: false,
+                population: record.population.into_inner(),
+                capital: record.capital.into_inner(),
+                currency: record.currency.into_inner(),
+            })
+            .collect();
+
+        let mut index = HashMap::new();
+        for (i, country) in countries.iter().enumerate() {
+            index.insert(country.name.to_lowercase(), i);
+            index.insert(country.iso2.to_lowercase(), i);
+            index.insert(country.iso3.to_lowercase(), i);
+            for alias in country.aliases.iter().chain(&country.native_names) {
+                index.entry(alias.to_lowercase()).or_insert(i);
+            }
+        }
+
+        Ok(CountryCatalogue {
+            version: *file.version.get_ref(),
+            countries,
+            index,
+        })
+    }
+
+    pub fn all(&self) -> &[Country] {
+        &self.countries
+    }
+
+    pub fn len(&self) -> usize {
+        self.countries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.countries.is_empty()
+    }
+
+    // Exact lookup by name, alias or ISO code, ignoring case
+    pub fn get(&self, query: &str) -> Option<&Country> {
+        self.index
+            .get(&query.trim().to_lowercase())
+            .map(|&i| &self.countries[i])
+    }
+
+    // Countries keyed by name
+    pub fn as_map(&self) -> HashMap<String, Country> {
+        self.countries
+            .iter()
+            .map(|country| (country.name.clone(), country.clone()))
+            .collect()
+    }
+}
+
+static CATALOGUE: OnceLock<CountryCatalogue> = OnceLock::new();
+
+// Load the catalogue at startup, from `path` if given or the bundled dataset
+pub fn load(path: Option<&str>) -> Result<&'static CountryCatalogue, Box<dyn std::error::Error>> {
+    let loaded = match path {
+        Some(path) => CountryCatalogue::parse(path, &fs::read_to_string(path)?)?,
+        None => CountryCatalogue::parse("countries.toml", BUNDLED)?,
+    };
+
+    CATALOGUE
+        .set(loaded)
+        .map_err(|_| "Country catalogue already loaded")?;
+    Ok(catalogue())
+}
+
+pub fn catalogue() -> &'static CountryCatalogue {
+    CATALOGUE.get_or_init(|| {
+        CountryCatalogue::parse("countries.toml", BUNDLED).expect("bundled country catalogue is valid")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KENYA: &str = r#"
+version = 1
+
+[[country]]
+name = "Kenya"
+iso2 = "KE"
+iso3 = "KEN"
+continent = "Africa"
+capital = "Nairobi"
+currency = "Kenyan Shilling"
+languages = ["Swahili", "English"]
+population = 55_100_000
+flag = "🇰🇪"
+facts = ["Birthplace of marathon champions"]
+traditions = ["Maasai jumping dance"]
+"#;
+
+    #[test]
+    fn bundled_catalogue_is_valid() {
+        let catalogue = CountryCatalogue::parse("countries.toml", BUNDLED).unwrap();
+        assert!(catalogue.len() >= 195);
+        assert_eq!(catalogue.get("ke").unwrap().name, "Kenya");
+        assert_eq!(catalogue.get("BRA").unwrap().name, "Brazil");
+        assert_eq!(catalogue.get("japan").unwrap().capital, "Tokyo");
+    }
+
+    #[test]
+    fn validation_errors_point_at_the_line() {
+        let source = KENYA
+            .replace("iso3 = \"KEN\"", "iso3 = \"KENYA\"")
+            .replace("continent = \"Africa\"", "continent = \"Atlantis\"");
+
+        let err = CountryCatalogue::parse("test.toml", &source).err().unwrap();
+        assert_eq!(err.issues.len(), 2);
+        assert_eq!(err.issues[0].line, 7);
+        assert!(err.issues[0].message.contains("iso3"));
+        assert_eq!(err.issues[1].line, 8);
+        assert!(err.to_string().starts_with("test.toml:7: iso3 must be three uppercase letters"));
+    }
+
+    #[test]
+    fn flag_must_match_iso_code() {
+        let source = KENYA.replace("flag = \"🇰🇪\"", "flag = \"🇯🇵\"");
+
+        let err = CountryCatalogue::parse("test.toml", &source).err().unwrap();
+        assert_eq!(err.issues, vec![CatalogueIssue { line: 13, message: "flag does not match iso2 code \"KE\"".to_string() }]);
+    }
+
+    #[test]
+    fn duplicates_and_versions_are_rejected() {
+        let source = format!("{}\n{}", KENYA, KENYA.replace("version = 1", "")).replace("version = 1", "version = 2");
+
+        let err = CountryCatalogue::parse("test.toml", &source).err().unwrap();
+        let messages: Vec<&str> = err.issues.iter().map(|issue| issue.message.as_str()).collect();
+        assert!(messages[0].starts_with("unsupported catalogue version 2"));
+        assert!(messages.contains(&"duplicate country \"Kenya\""));
+        assert!(messages.contains(&"duplicate iso2 code \"KE\""));
+    }
+
+    #[test]
+    fn syntax_errors_report_a_line() {
+        let source = KENYA.replace("population = 55_100_000", "population = lots");
+
+        let err = CountryCatalogue::parse("test.toml", &source).err().unwrap();
+        assert_eq!(err.issues[0].line, 12);
+    }
+}
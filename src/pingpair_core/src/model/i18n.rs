@@ -0,0 +1,245 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::OnceLock;
+
+// Every message exists in this locale; the others fall back to it
+pub const DEFAULT_LOCALE: &str = "en";
+
+// The locales shipped with the binary, by code
+const BUNDLED: [(&str, &str); 4] = [
+    ("en", include_str!("../../data/locales/en.toml")),
+    ("es", include_str!("../../data/locales/es.toml")),
+    ("fr", include_str!("../../data/locales/fr.toml")),
+    ("sw", include_str!("../../data/locales/sw.toml")),
+];
+
+// One locale's messages, keyed by "section.name"
+pub struct Locale {
+    pub code: String,
+    // Name of the language in itself, e.g. "Español"
+    pub name: String,
+    messages: HashMap<String, String>,
+}
+
+impl Locale {
+    // A locale file is a `name` plus sections of message templates. Sections
+    // nest, so `[exchange.level]` `native = ...` is the key
+    // "exchange.level.native".
+    pub fn parse(code: &str, source: &str) -> Result<Self, String> {
+        let table: toml::Table = toml::from_str(source).map_err(|e| format!("{}.toml: {}", code, e.message()))?;
+
+        let mut name = None;
+        let mut messages = HashMap::new();
+        for (key, value) in table {
+            match value {
+                toml::Value::String(value) if key == "name" => name = Some(value),
+                toml::Value::Table(section) => flatten(code, &key, section, &mut messages)?,
+                _ => return Err(format!("{}.toml: \"{}\" must be a section of messages", code, key)),
+            }
+        }
+
+        Ok(Locale {
+            code: code.to_string(),
+            name: name.ok_or_else(|| format!("{}.toml: missing name", code))?,
+            messages,
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.messages.get(key).map(String::as_str)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.messages.keys()
+    }
+}
+
+fn flatten(code: &str, prefix: &str, section: toml::Table, messages: &mut HashMap<String, String>) -> Result<(), String> {
+    for (key, value) in section {
+        let key = format!("{}.{}", prefix, key);
+        match value {
+            toml::Value::String(text) => {
+                messages.insert(key, text);
+            }
+            toml::Value::Table(section) => flatten(code, &key, section, messages)?,
+            _ => return Err(format!("{}.toml: message \"{}\" must be a string", code, key)),
+        }
+    }
+    Ok(())
+}
+
+// The `{name}` placeholders of a template
+pub fn placeholders(template: &str) -> BTreeSet<&str> {
+    let mut found = BTreeSet::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        match rest.find('}') {
+            Some(end) if is_placeholder(&rest[..end]) => {
+                found.insert(&rest[..end]);
+                rest = &rest[end + 1..];
+            }
+            _ => {}
+        }
+    }
+    found
+}
+
+fn is_placeholder(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+// Fill in `{name}` placeholders. Ones without an argument are left as they
+// are so a missing value is visible rather than silently dropped.
+pub fn render(template: &str, args: &[(&str, String)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest[1..].find('}').and_then(|end| {
+            let name = &rest[1..end + 1];
+            let value = args.iter().find(|(arg, _)| *arg == name).map(|(_, value)| value)?;
+            Some((value, end + 2))
+        });
+        match value {
+            Some((value, consumed)) => {
+                rendered.push_str(value);
+                rest = &rest[consumed..];
+            }
+            None => {
+                rendered.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+pub struct Messages {
+    locales: BTreeMap<String, Locale>,
+}
+
+impl Messages {
+    pub fn parse(sources: &[(&str, &str)]) -> Result<Self, String> {
+        let mut locales = BTreeMap::new();
+        for (code, source) in sources {
+            locales.insert(code.to_string(), Locale::parse(code, source)?);
+        }
+        if !locales.contains_key(DEFAULT_LOCALE) {
+            return Err(format!("the default locale \"{}\" is missing", DEFAULT_LOCALE));
+        }
+        Ok(Messages { locales })
+    }
+
+    pub fn locales(&self) -> impl Iterator<Item = &Locale> {
+        self.locales.values()
+    }
+
+    // The shipped locale for a user's choice: its code in any case, a
+    // regional variant like "es-MX" or "pt_BR", or the language's own name.
+    pub fn find(&self, requested: &str) -> Option<&Locale> {
+        let requested = requested.trim().to_lowercase();
+        let language = requested.split(['-', '_']).next().unwrap_or_default();
+
+        self.locales.get(requested.as_str())
+            .or_else(|| self.locales.get(language))
+            .or_else(|| self.locales.values().find(|locale| locale.name.to_lowercase() == requested))
+    }
+
+    // The template for `key` in `locale`, falling back to the default locale
+    pub fn template(&self, locale: &str, key: &str) -> Option<&str> {
+        self.find(locale)
+            .and_then(|locale| locale.get(key))
+            .or_else(|| self.locales[DEFAULT_LOCALE].get(key))
+    }
+
+    // Missing keys render as the key itself so they stand out in replies
+    pub fn text(&self, locale: &str, key: &str, args: &[(&str, String)]) -> String {
+        match self.template(locale, key) {
+            Some(template) => render(template, args),
+            None => key.to_string(),
+        }
+    }
+}
+
+static MESSAGES: OnceLock<Messages> = OnceLock::new();
+
+pub fn messages() -> &'static Messages {
+    MESSAGES.get_or_init(|| Messages::parse(&BUNDLED).expect("bundled locales are valid"))
+}
+
+// A message in the user's locale, e.g.
+// `t(&user.locale, "pair.partner", &[("name", partner.name.clone())])`
+pub fn t(locale: &str, key: &str, args: &[(&str, String)]) -> String {
+    messages().text(locale, key, args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(pairs: &[(&'static str, &str)]) -> Vec<(&'static str, String)> {
+        pairs.iter().map(|(name, value)| (*name, value.to_string())).collect()
+    }
+
+    #[test]
+    fn every_locale_has_every_message_with_the_same_placeholders() {
+        let messages = messages();
+        let default = &messages.locales[DEFAULT_LOCALE];
+
+        for locale in messages.locales() {
+            let missing: Vec<&String> = default.keys().filter(|key| locale.get(key).is_none()).collect();
+            let extra: Vec<&String> = locale.keys().filter(|key| default.get(key).is_none()).collect();
+            assert!(missing.is_empty(), "{} is missing {:?}", locale.code, missing);
+            assert!(extra.is_empty(), "{} has unknown keys {:?}", locale.code, extra);
+
+            for key in default.keys() {
+                assert_eq!(
+                    placeholders(locale.get(key).unwrap()),
+                    placeholders(default.get(key).unwrap()),
+                    "placeholders of {} in {}",
+                    key,
+                    locale.code
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn locales_fall_back_to_the_default() {
+        let messages = Messages::parse(&[
+            ("en", "name = \"English\"\n[pair]\npartner = \"**Partner:** {name}\"\nno_partners = \"Nobody\""),
+            ("es", "name = \"Español\"\n[pair]\npartner = \"**Pareja:** {name}\""),
+        ])
+        .unwrap();
+        let name = args(&[("name", "Amina")]);
+
+        assert_eq!(messages.text("es", "pair.partner", &name), "**Pareja:** Amina");
+        assert_eq!(messages.text("es-MX", "pair.partner", &name), "**Pareja:** Amina");
+        assert_eq!(messages.text("español", "pair.partner", &name), "**Pareja:** Amina");
+        assert_eq!(messages.text("es", "pair.no_partners", &[]), "Nobody");
+        assert_eq!(messages.text("pt-BR", "pair.partner", &name), "**Partner:** Amina");
+        assert_eq!(messages.text("es", "pair.missing", &[]), "pair.missing");
+        assert_eq!(messages.find("ES").unwrap().code, "es");
+        assert!(messages.find("pt").is_none());
+    }
+
+    #[test]
+    fn rendering_fills_known_placeholders_only() {
+        let rendered = render("{a} and {b}, {unknown} {not a placeholder} {", &args(&[("a", "1"), ("b", "{a}")]));
+        assert_eq!(rendered, "1 and {a}, {unknown} {not a placeholder} {");
+        assert_eq!(
+            placeholders("{country} {flag} {Not} {} {country}").into_iter().collect::<Vec<&str>>(),
+            vec!["country", "flag"]
+        );
+    }
+
+    #[test]
+    fn invalid_locale_files_are_rejected() {
+        assert!(Locale::parse("xx", "[pair]\npartner = \"Hi\"").is_err());
+        assert!(Locale::parse("xx", "name = \"X\"\n[pair]\ncount = 3").is_err());
+        assert!(Messages::parse(&[("es", "name = \"Español\"")]).is_err());
+    }
+}
@@ -0,0 +1,298 @@
+use crate::model::catalogue::{self, line_of, CatalogueError, CatalogueIssue, CountryCatalogue};
+use crate::model::rng::{self, Rng};
+use crate::model::types::IcebreakerQuestion;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fs;
+use std::sync::OnceLock;
+use toml::Spanned;
+
+// Schema version of src/pingpair_core/data/icebreakers.toml understood by this build
+pub const SUPPORTED_VERSION: u32 = 1;
+
+// Icebreakers attached to every new pairing
+pub const PER_PAIRING: usize = 3;
+
+const PLACEHOLDER: &str = "{country}";
+
+// The bank shipped with the binary
+const BUNDLED: &str = include_str!("../../data/icebreakers.toml");
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BankFile {
+    version: Spanned<u32>,
+    #[serde(rename = "icebreaker", default)]
+    icebreakers: Vec<IcebreakerRecord>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IcebreakerRecord {
+    id: Spanned<String>,
+    question: Spanned<String>,
+    #[serde(default)]
+    country: Option<Spanned<String>>,
+    #[serde(default)]
+    interests: Vec<String>,
+}
+
+pub struct IcebreakerBank {
+    pub version: u32,
+    questions: Vec<IcebreakerQuestion>,
+}
+
+impl IcebreakerBank {
+    // Per-country questions name their country by ISO code or name; it must
+    // exist in `countries`.
+    pub fn parse(source_name: &str, source: &str, countries: &CountryCatalogue) -> Result<Self, CatalogueError> {
+        let error = |issues: Vec<CatalogueIssue>| CatalogueError {
+            source_name: source_name.to_string(),
+            issues,
+        };
+
+        let file: BankFile = toml::from_str(source).map_err(|e| {
+            error(vec![CatalogueIssue {
+                line: e.span().map(|span| line_of(source, span.start)).unwrap_or(1),
+                message: e.message().to_string(),
+            }])
+        })?;
+
+        let mut issues = Vec::new();
+        let mut issue = |span: std::ops::Range<usize>, message: String| {
+            issues.push(CatalogueIssue {
+                line: line_of(source, span.start),
+                message,
+            });
+        };
+
+        if *file.version.get_ref() != SUPPORTED_VERSION {
+            issue(
+                file.version.span(),
+                format!("unsupported icebreaker bank version {} (expected {})", file.version.get_ref(), SUPPORTED_VERSION),
+            );
+        }
+
+        let mut ids = HashSet::new();
+        let mut questions = Vec::new();
+        for record in &file.icebreakers {
+            let id = record.id.get_ref();
+            let question = record.question.get_ref();
+
+            if id.trim().is_empty() {
+                issue(record.id.span(), "id must not be empty".to_string());
+            } else if !ids.insert(id.clone()) {
+                issue(record.id.span(), format!("duplicate icebreaker id \"{}\"", id));
+            }
+
+            if question.trim().is_empty() {
+                issue(record.question.span(), "question must not be empty".to_string());
+            } else if question.replace(PLACEHOLDER, "").contains(['{', '}']) {
+                issue(record.question.span(), format!("only {} may be used as a placeholder", PLACEHOLDER));
+            }
+
+            let country = match &record.country {
+                Some(country) => match countries.get(country.get_ref()) {
+                    Some(found) => Some(found.name.clone()),
+                    None => {
+                        issue(country.span(), format!("unknown country \"{}\"", country.get_ref()));
+                        None
+                    }
+                },
+                None => None,
+            };
+
+            questions.push(IcebreakerQuestion {
+                id: id.clone(),
+                question: question.clone(),
+                country_specific: country.is_some() || question.contains(PLACEHOLDER),
+                country,
+                interests: record.interests.iter().map(|interest| interest.to_lowercase()).collect(),
+            });
+        }
+
+        if !issues.is_empty() {
+            return Err(error(issues));
+        }
+
+        Ok(IcebreakerBank {
+            version: *file.version.get_ref(),
+            questions,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.questions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.questions.is_empty()
+    }
+
+    // Up to `count` unseen questions for a pairing about `country`, best fit
+    // first: questions about that country, then ones on the partners' shared
+    // interests, then spotlight templates, then general questions. Questions
+    // on interests the partners don't share come last.
+    pub fn select(
+        &self,
+        country: Option<&str>,
+        shared_interests: &[String],
+        seen: &HashSet<String>,
+        count: usize,
+        rng: &mut dyn Rng,
+    ) -> Vec<IcebreakerQuestion> {
+        let shared: HashSet<String> = shared_interests.iter().map(|interest| interest.to_lowercase()).collect();
+        let mut tiers: [Vec<&IcebreakerQuestion>; 5] = Default::default();
+
+        for question in self.questions.iter().filter(|question| !seen.contains(&question.id)) {
+            let tier = match (&question.country, question.question.contains(PLACEHOLDER)) {
+                (Some(name), _) if Some(name.as_str()) == country => 0,
+                (Some(_), _) => continue,
+                (None, true) if country.is_none() => continue,
+                _ if question.interests.iter().any(|interest| shared.contains(interest)) => 1,
+                (None, true) => 2,
+                _ if question.interests.is_empty() => 3,
+                _ => 4,
+            };
+            tiers[tier].push(question);
+        }
+
+        let mut selected = Vec::new();
+        for mut tier in tiers {
+            rng::shuffle(rng, &mut tier);
+            selected.extend(tier);
+        }
+
+        selected
+            .into_iter()
+            .take(count)
+            .map(|question| IcebreakerQuestion {
+                question: question.question.replace(PLACEHOLDER, country.unwrap_or_default()),
+                ..question.clone()
+            })
+            .collect()
+    }
+}
+
+static BANK: OnceLock<IcebreakerBank> = OnceLock::new();
+
+// Load the bank at startup, from `path` if given or the bundled file. Call
+// after the country catalogue is loaded.
+pub fn load(path: Option<&str>) -> Result<&'static IcebreakerBank, Box<dyn std::error::Error>> {
+    let countries = catalogue::catalogue();
+    let loaded = match path {
+        Some(path) => IcebreakerBank::parse(path, &fs::read_to_string(path)?, countries)?,
+        None => IcebreakerBank::parse("icebreakers.toml", BUNDLED, countries)?,
+    };
+
+    BANK.set(loaded).map_err(|_| "Icebreaker bank already loaded")?;
+    Ok(bank())
+}
+
+pub fn bank() -> &'static IcebreakerBank {
+    BANK.get_or_init(|| {
+        IcebreakerBank::parse("icebreakers.toml", BUNDLED, catalogue::catalogue()).expect("bundled icebreaker bank is valid")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::rng::SeededRng;
+
+    const SMALL: &str = r#"
+version = 1
+
+[[icebreaker]]
+id = "general-01"
+question = "What made you smile this week?"
+
+[[icebreaker]]
+id = "music-01"
+question = "Do you play an instrument?"
+interests = ["Music"]
+
+[[icebreaker]]
+id = "spotlight-01"
+question = "What would you do first in {country}?"
+
+[[icebreaker]]
+id = "ke-01"
+question = "Have you ever run a marathon?"
+country = "KE"
+
+[[icebreaker]]
+id = "jp-01"
+question = "Tokyo or Kyoto?"
+country = "JP"
+
+[[icebreaker]]
+id = "books-01"
+question = "What are you reading?"
+interests = ["books"]
+"#;
+
+    fn small() -> IcebreakerBank {
+        IcebreakerBank::parse("test.toml", SMALL, catalogue::catalogue()).unwrap()
+    }
+
+    fn ids(questions: &[IcebreakerQuestion]) -> Vec<&str> {
+        questions.iter().map(|question| question.id.as_str()).collect()
+    }
+
+    #[test]
+    fn bundled_bank_is_valid() {
+        let bank = IcebreakerBank::parse("icebreakers.toml", BUNDLED, catalogue::catalogue()).unwrap();
+        assert!(bank.len() >= 50);
+        assert!(bank.questions.iter().any(|question| question.country.as_deref() == Some("Kenya")));
+    }
+
+    #[test]
+    fn selection_prefers_country_then_shared_interests() {
+        let picked = small().select(Some("Kenya"), &["music".to_string()], &HashSet::new(), 4, &mut SeededRng::new(1));
+
+        assert_eq!(ids(&picked), vec!["ke-01", "music-01", "spotlight-01", "general-01"]);
+        assert_eq!(picked[2].question, "What would you do first in Kenya?");
+        assert!(picked[2].country_specific);
+    }
+
+    #[test]
+    fn seen_questions_are_never_repeated() {
+        let bank = small();
+        let mut seen = HashSet::new();
+        let mut asked = Vec::new();
+
+        loop {
+            let picked = bank.select(Some("Kenya"), &[], &seen, 1, &mut SeededRng::new(4));
+            let Some(question) = picked.into_iter().next() else { break };
+            assert!(seen.insert(question.id.clone()));
+            asked.push(question.id);
+        }
+
+        // Everything except the other country's question, with unshared
+        // interests last
+        assert_eq!(asked.len(), 5);
+        assert!(!asked.contains(&"jp-01".to_string()));
+        assert!(asked[3..].contains(&"books-01".to_string()));
+    }
+
+    #[test]
+    fn templates_need_a_country() {
+        let picked = small().select(None, &[], &HashSet::new(), 10, &mut SeededRng::new(1));
+        assert!(!ids(&picked).contains(&"spotlight-01"));
+        assert!(!ids(&picked).contains(&"ke-01"));
+    }
+
+    #[test]
+    fn invalid_entries_point_at_the_line() {
+        let source = SMALL
+            .replace("country = \"JP\"", "country = \"Atlantis\"")
+            .replace("{country}?", "{city}?")
+            .replace("id = \"books-01\"", "id = \"general-01\"");
+
+        let err = IcebreakerBank::parse("test.toml", &source, catalogue::catalogue()).err().unwrap();
+        let lines: Vec<usize> = err.issues.iter().map(|issue| issue.line).collect();
+        assert_eq!(lines, vec![15, 25, 28]);
+        assert!(err.to_string().contains("test.toml:25: unknown country \"Atlantis\""));
+    }
+}
@@ -1,5 +1,13 @@
 pub mod types;
 pub mod state;
+pub mod availability;
+pub mod catalogue;
+pub mod clock;
+pub mod i18n;
+pub mod icebreakers;
+pub mod rng;
+pub mod snapshot;
+pub mod spotlight;
 
 // Re-export key functions and types
 pub use state::{get_user, create_user, update_user, create_pairing, complete_pairing, initialize_spotlight_countries};
@@ -0,0 +1,68 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+// Source of randomness for selection and matching. Anything that must be
+// unguessable (meeting rooms, signing keys) uses the OS CSPRNG directly.
+pub trait Rng: Send {
+    fn next_u64(&mut self) -> u64;
+
+    // Uniform in 0..bound; bound must be non-zero
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+// Fisher-Yates
+pub fn shuffle<T, R: Rng + ?Sized>(rng: &mut R, items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+        let j = rng.below(i + 1);
+        items.swap(i, j);
+    }
+}
+
+// SplitMix64: tiny, seedable and serialisable with the state
+#[derive(Clone, Debug, Default, CandidType, Serialize, Deserialize)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        SeededRng { state: seed }
+    }
+
+    // Seeded from the OS so production runs differ
+    pub fn from_entropy() -> Self {
+        let mut seed = [0u8; 8];
+        getrandom::getrandom(&mut seed).expect("OS random number generator unavailable");
+        SeededRng::new(u64::from_le_bytes(seed))
+    }
+}
+
+impl Rng for SeededRng {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_rng_is_reproducible() {
+        let mut a = SeededRng::new(9);
+        let mut b = SeededRng::new(9);
+        let mut items_a: Vec<u32> = (0..20).collect();
+        let mut items_b = items_a.clone();
+
+        shuffle(&mut a, &mut items_a);
+        shuffle(&mut b, &mut items_b);
+        assert_eq!(items_a, items_b);
+        assert_ne!(items_a, (0..20).collect::<Vec<u32>>());
+    }
+}
@@ -0,0 +1,174 @@
+use crate::metrics;
+use crate::model::state::{self, PingPairState};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// Upgrades a snapshot's state from one layout version to the next
+type Migration = fn(Value) -> Result<Value, String>;
+
+// MIGRATIONS[n] turns a version n + 1 snapshot into version n + 2. Fields
+// added to the store with `#[serde(default)]` load from older snapshots as
+// they are; any other layout change appends a step here.
+const MIGRATIONS: &[Migration] = &[];
+
+// Layout version written by `encode`
+pub const CURRENT_VERSION: u32 = MIGRATIONS.len() as u32 + 1;
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    version: u32,
+    state: Value,
+}
+
+pub fn encode(state: &PingPairState) -> Result<Vec<u8>, String> {
+    let snapshot = Snapshot {
+        version: CURRENT_VERSION,
+        state: serde_json::to_value(state).map_err(|e| format!("Cannot encode state: {}", e))?,
+    };
+    serde_json::to_vec(&snapshot).map_err(|e| format!("Cannot encode state: {}", e))
+}
+
+pub fn decode(bytes: &[u8]) -> Result<PingPairState, String> {
+    let snapshot: Snapshot = serde_json::from_slice(bytes).map_err(|e| format!("Cannot read snapshot: {}", e))?;
+    let state = migrate(snapshot.version, snapshot.state, MIGRATIONS)?;
+    serde_json::from_value(state).map_err(|e| format!("Cannot read snapshot version {}: {}", snapshot.version, e))
+}
+
+fn migrate(version: u32, mut state: Value, migrations: &[Migration]) -> Result<Value, String> {
+    let current = migrations.len() as u32 + 1;
+    if version == 0 || version > current {
+        return Err(format!("Snapshot version {} is not supported by this build (1 to {})", version, current));
+    }
+
+    for (step, migration) in migrations.iter().enumerate().skip(version as usize - 1) {
+        state = migration(state).map_err(|e| format!("Cannot migrate snapshot from version {}: {}", step + 1, e))?;
+    }
+    Ok(state)
+}
+
+// The store as written to stable memory before a canister upgrade, or to
+// the bot server's state file
+pub fn save() -> Result<Vec<u8>, String> {
+    let timer = metrics::Timer::start();
+    let bytes = encode(&state::export_state())?;
+    metrics::SNAPSHOT_DURATION.observe(&[("operation", "save")], timer.seconds());
+    metrics::SNAPSHOT_BYTES.set(&[], bytes.len() as f64);
+    Ok(bytes)
+}
+
+// Replace the store with a snapshot from `save`, e.g. after an upgrade or
+// a restart
+pub fn load(bytes: &[u8]) -> Result<(), String> {
+    let timer = metrics::Timer::start();
+    state::restore_state(decode(bytes)?)?;
+    metrics::SNAPSHOT_DURATION.observe(&[("operation", "load")], timer.seconds());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::clock::FakeClock;
+    use crate::model::state::testing;
+    use candid::Principal;
+    use std::sync::Arc;
+
+    // 2026-10-19T09:00:00Z
+    const START: u64 = 1_792_400_400 * 1_000_000_000;
+
+    fn user_id(id: u8) -> String {
+        Principal::from_slice(&[id]).to_text()
+    }
+
+    fn as_json(state: &PingPairState) -> Value {
+        serde_json::to_value(state).unwrap()
+    }
+
+    #[test]
+    fn state_survives_an_upgrade() {
+        let _guard = testing::fresh_state(Arc::new(FakeClock::new(START)), 1);
+        state::initialize_spotlight_countries();
+        state::create_user(Principal::from_slice(&[1]), "Amina".to_string());
+        state::create_user(Principal::from_slice(&[2]), "Kenji".to_string());
+        state::set_user_active(&user_id(2), false);
+        let session = state::create_new_session();
+        state::set_session_pick(&user_id(1), &session.featured_countries[0]);
+        let pairing = state::create_pairing(&user_id(1), &user_id(2), "Kenya").unwrap();
+        let before = state::export_state();
+
+        let bytes = save().unwrap();
+        // An upgrade starts the canister from an empty heap
+        drop(_guard);
+        let _guard = testing::fresh_state(Arc::new(FakeClock::new(START)), 2);
+        assert!(state::get_user(&user_id(1)).is_none());
+        load(&bytes).unwrap();
+
+        assert_eq!(as_json(&state::export_state()), as_json(&before));
+        assert_eq!(state::get_user(&user_id(1)).unwrap().name, "Amina");
+        assert!(!state::get_user(&user_id(2)).unwrap().active);
+        assert_eq!(state::get_pairing(&pairing.id).unwrap().country, "Kenya");
+        assert_eq!(state::get_current_session().unwrap().id, session.id);
+    }
+
+    #[test]
+    fn the_availability_index_is_rebuilt_not_saved() {
+        let _guard = testing::fresh_state(Arc::new(FakeClock::new(START)), 1);
+        let mut user = state::create_user(Principal::from_slice(&[1]), "Amina".to_string());
+        user.country = "Kenya".to_string();
+        state::update_user(&user_id(1), user);
+        let mut user = state::create_user(Principal::from_slice(&[2]), "Kenji".to_string());
+        user.country = "Japan".to_string();
+        state::update_user(&user_id(2), user);
+        state::set_user_active(&user_id(2), false);
+
+        let bytes = save().unwrap();
+        let saved: Value = serde_json::from_slice(&bytes).unwrap();
+        assert!(saved["state"].get("availability").is_none());
+
+        drop(_guard);
+        let _guard = testing::fresh_state(Arc::new(FakeClock::new(START)), 2);
+        load(&bytes).unwrap();
+        assert_eq!(state::get_available_countries(), vec!["Kenya".to_string()]);
+    }
+
+    #[test]
+    fn fields_missing_from_older_snapshots_default() {
+        let older = br#"{"version": 1, "state": {"users": {}, "session_picks": {"u1": "Kenya"}}}"#;
+
+        let state = decode(older).unwrap();
+        assert_eq!(state.session_picks["u1"], "Kenya");
+        assert!(state.quiz_progress.is_empty());
+        assert!(state.current_session.is_none());
+    }
+
+    #[test]
+    fn older_versions_are_migrated_in_order() {
+        fn rename_picks(mut state: Value) -> Result<Value, String> {
+            let picks = state["picks"].take();
+            state["session_picks"] = picks;
+            Ok(state)
+        }
+        fn tag(mut state: Value) -> Result<Value, String> {
+            state["past_sessions"] = serde_json::json!([]);
+            Ok(state)
+        }
+        let migrations: &[Migration] = &[rename_picks, tag];
+
+        let v1 = serde_json::json!({"picks": {"u1": "Kenya"}});
+        let migrated = migrate(1, v1, migrations).unwrap();
+        assert_eq!(migrated["session_picks"]["u1"], "Kenya");
+        assert_eq!(migrated["past_sessions"], serde_json::json!([]));
+
+        // A version 2 snapshot only needs the second step
+        let v2 = serde_json::json!({"session_picks": {}});
+        assert!(migrate(2, v2, migrations).unwrap().get("picks").is_none());
+    }
+
+    #[test]
+    fn unknown_versions_and_garbage_are_rejected() {
+        let newer = format!(r#"{{"version": {}, "state": {{}}}}"#, CURRENT_VERSION + 1);
+        assert!(decode(newer.as_bytes()).unwrap_err().contains("not supported"));
+        assert!(decode(br#"{"version": 0, "state": {}}"#).is_err());
+        assert!(decode(b"not a snapshot").is_err());
+    }
+}
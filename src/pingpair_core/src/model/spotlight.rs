@@ -0,0 +1,203 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::model::rng::{self, SeededRng};
+use crate::model::types::Country;
+
+// Sessions and ping time are shared by everyone until the bot can tell
+// communities apart.
+pub const DEFAULT_COMMUNITY: &str = "global";
+
+// What one community has still to see this round
+#[derive(Clone, Debug, Default, CandidType, Serialize, Deserialize)]
+struct CommunityDeck {
+    // Countries not yet featured this round, in draw order
+    countries: Vec<String>,
+    // Most recently featured country, so a new round never starts with it
+    last: Option<String>,
+    // country -> indices of facts not yet shown this round
+    facts: HashMap<String, Vec<u32>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spotlight {
+    pub country: Country,
+    pub facts: Vec<String>,
+}
+
+// Rotates countries and facts through a shuffled deck per community: every
+// country is featured once before any repeats, and likewise for each
+// country's facts. Countries where members are active are drawn first
+// within a round.
+#[derive(Clone, Debug, Default, CandidType, Serialize, Deserialize)]
+pub struct SpotlightPlanner {
+    rng: SeededRng,
+    communities: HashMap<String, CommunityDeck>,
+}
+
+impl SpotlightPlanner {
+    pub fn new(seed: u64) -> Self {
+        SpotlightPlanner {
+            rng: SeededRng::new(seed),
+            communities: HashMap::new(),
+        }
+    }
+
+    // Draw the next `count` distinct countries for a community
+    pub fn next_countries(
+        &mut self,
+        community: &str,
+        countries: &[Country],
+        active: &HashSet<String>,
+        count: usize,
+    ) -> Vec<String> {
+        let count = count.min(countries.len());
+        let mut drawn: Vec<String> = Vec::with_capacity(count);
+
+        while drawn.len() < count {
+            let deck = self.communities.entry(community.to_string()).or_default();
+
+            // Drop countries that have left the catalogue since the deck was dealt
+            deck.countries.retain(|name| countries.iter().any(|country| country.name == *name));
+            if deck.countries.iter().all(|name| drawn.contains(name)) {
+                let mut fresh: Vec<String> = countries.iter().map(|country| country.name.clone()).collect();
+                rng::shuffle(&mut self.rng, &mut fresh);
+
+                // Don't repeat the previous spotlight back-to-back across rounds
+                if fresh.len() > 1 && fresh.first() == deck.last.as_ref() {
+                    let last = fresh.len() - 1;
+                    fresh.swap(0, last);
+                }
+                deck.countries.extend(fresh);
+            }
+
+            let position = deck.countries
+                .iter()
+                .position(|name| active.contains(name) && !drawn.contains(name))
+                .or_else(|| deck.countries.iter().position(|name| !drawn.contains(name)))
+                .expect("deck was refilled");
+            let name = deck.countries.remove(position);
+            deck.last = Some(name.clone());
+            drawn.push(name);
+        }
+
+        drawn
+    }
+
+    // Draw `count` facts for a country without repeating any until all of
+    // its facts have been shown to the community.
+    pub fn next_facts(&mut self, community: &str, country: &Country, count: usize) -> Vec<String> {
+        let total = country.fun_facts.len();
+        let count = count.min(total);
+        let deck = self.communities.entry(community.to_string()).or_default();
+        let remaining = deck.facts.entry(country.name.clone()).or_default();
+
+        let mut drawn: Vec<u32> = Vec::with_capacity(count);
+        while drawn.len() < count {
+            remaining.retain(|&index| (index as usize) < total);
+            if remaining.iter().all(|index| drawn.contains(index)) {
+                let mut fresh: Vec<u32> = (0..total as u32).collect();
+                rng::shuffle(&mut self.rng, &mut fresh);
+                remaining.extend(fresh);
+            }
+
+            let position = remaining
+                .iter()
+                .position(|index| !drawn.contains(index))
+                .expect("facts were refilled");
+            drawn.push(remaining.remove(position));
+        }
+
+        drawn
+            .into_iter()
+            .map(|index| country.fun_facts[index as usize].clone())
+            .collect()
+    }
+
+    // The next country and facts to spotlight for a community
+    pub fn next_spotlight(
+        &mut self,
+        community: &str,
+        countries: &[Country],
+        active: &HashSet<String>,
+        facts: usize,
+    ) -> Option<Spotlight> {
+        let name = self.next_countries(community, countries, active, 1).pop()?;
+        let country = countries.iter().find(|country| country.name == name)?.clone();
+        let facts = self.next_facts(community, &country, facts);
+
+        Some(Spotlight { country, facts })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::catalogue;
+
+    fn countries() -> Vec<Country> {
+        catalogue::catalogue().all()[..12].to_vec()
+    }
+
+    #[test]
+    fn every_country_is_featured_once_per_round() {
+        let countries = countries();
+        let mut planner = SpotlightPlanner::new(7);
+
+        let mut first_round: Vec<String> = (0..countries.len())
+            .map(|_| planner.next_countries("c1", &countries, &HashSet::new(), 1).remove(0))
+            .collect();
+        let next_round_start = planner.next_countries("c1", &countries, &HashSet::new(), 1).remove(0);
+
+        assert_ne!(&next_round_start, first_round.last().unwrap());
+        first_round.sort();
+        first_round.dedup();
+        assert_eq!(first_round.len(), countries.len());
+    }
+
+    #[test]
+    fn facts_do_not_repeat_until_exhausted() {
+        let country = catalogue::catalogue().get("Kenya").unwrap().clone();
+        let mut planner = SpotlightPlanner::new(3);
+
+        let mut shown: Vec<String> = Vec::new();
+        while shown.len() < country.fun_facts.len() {
+            let facts = planner.next_facts("c1", &country, 1);
+            assert!(!shown.contains(&facts[0]));
+            shown.extend(facts);
+        }
+
+        // A draw straddling two rounds still has no duplicates
+        let facts = planner.next_facts("c1", &country, country.fun_facts.len());
+        let unique: HashSet<&String> = facts.iter().collect();
+        assert_eq!(unique.len(), facts.len());
+    }
+
+    #[test]
+    fn active_countries_come_first_and_communities_are_independent() {
+        let countries = countries();
+        let active: HashSet<String> = [countries[5].name.clone()].into_iter().collect();
+        let mut planner = SpotlightPlanner::new(11);
+
+        assert_eq!(planner.next_countries("c1", &countries, &active, 1), vec![countries[5].name.clone()]);
+        assert_eq!(planner.next_countries("c2", &countries, &active, 1), vec![countries[5].name.clone()]);
+
+        let session = planner.next_countries("c1", &countries, &active, 3);
+        assert!(!session.contains(&countries[5].name));
+    }
+
+    #[test]
+    fn same_seed_same_plan() {
+        let countries = countries();
+        let plan = |seed| {
+            let mut planner = SpotlightPlanner::new(seed);
+            (0..5)
+                .map(|_| planner.next_spotlight("c1", &countries, &HashSet::new(), 2).unwrap())
+                .collect::<Vec<Spotlight>>()
+        };
+
+        assert_eq!(plan(42), plan(42));
+        assert_ne!(plan(42), plan(43));
+    }
+}
@@ -0,0 +1,646 @@
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::metrics;
+use crate::model::availability::{AvailabilityIndex, UNKNOWN_COUNTRY};
+use crate::model::{catalogue, icebreakers};
+use crate::model::clock::{Clock, SystemClock};
+use crate::model::i18n::DEFAULT_LOCALE;
+use crate::model::rng::{Rng, SeededRng};
+use crate::model::spotlight::{Spotlight, SpotlightPlanner, DEFAULT_COMMUNITY};
+use crate::model::types::{UserProfile, Pairing, PairingStatus, Session, LanguageExchange, MeetingLink, QuizProgress, IcebreakerQuestion};
+
+// Global state storage. Commands, the state file flusher and the shutdown
+// save run on different threads, so every access goes through the lock.
+static STATE: Mutex<Option<Store>> = Mutex::new(None);
+
+// The data and the services it runs on: the clock for every timestamp and
+// the RNG for selection and matching. The runtime passes both to
+// `initialize_state`; tests pass fakes.
+struct Store {
+    state: PingPairState,
+    clock: Arc<dyn Clock>,
+    rng: Box<dyn Rng>,
+}
+
+fn store() -> MutexGuard<'static, Option<Store>> {
+    STATE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Run `f` on the store under its lock, or return None before it is
+// initialized. `f` must not call back into the functions of this module.
+fn read<T>(f: impl FnOnce(&PingPairState) -> T) -> Option<T> {
+    store().as_ref().map(|store| f(&store.state))
+}
+
+fn write<T>(f: impl FnOnce(&mut PingPairState) -> T) -> Option<T> {
+    store().as_mut().map(|store| f(&mut store.state))
+}
+
+// Function to initialize the state on the given clock and RNG
+pub fn initialize_state(clock: Arc<dyn Clock>, mut rng: Box<dyn Rng>) {
+    let mut guard = store();
+    if guard.is_some() {
+        return;
+    }
+    let seed = rng.next_u64();
+    *guard = Some(Store {
+        state: PingPairState {
+            spotlight_planner: SpotlightPlanner::new(seed),
+            ..PingPairState::default()
+        },
+        clock,
+        rng,
+    });
+}
+
+pub fn is_initialized() -> bool {
+    store().is_some()
+}
+
+// Swap the store's RNG, e.g. once the canister has a seed from `raw_rand`
+pub fn set_rng(rng: Box<dyn Rng>) {
+    if let Some(store) = store().as_mut() {
+        store.rng = rng;
+    }
+}
+
+// Function to get current time in nanoseconds, from the store's clock; the
+// system clock until the store is initialized
+pub fn time() -> u64 {
+    match store().as_ref() {
+        Some(store) => store.clock.now(),
+        None => SystemClock.now(),
+    }
+}
+
+// Run `f` with the store's RNG, or one seeded from the OS before the store
+// is initialized. `f` must not call back into the functions of this module.
+pub fn with_rng<T>(f: impl FnOnce(&mut dyn Rng) -> T) -> T {
+    match store().as_mut() {
+        Some(store) => f(store.rng.as_mut()),
+        None => f(&mut SeededRng::from_entropy()),
+    }
+}
+
+// Fields default when missing so snapshots taken before a field existed
+// still load, see `model::snapshot`
+#[derive(Clone, Debug, Default, CandidType, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PingPairState {
+    pub users: HashMap<String, UserProfile>,
+    pub current_session: Option<Session>,
+    pub past_sessions: Vec<Session>,
+    pub active_pairings: HashMap<String, Pairing>,
+    pub completed_pairings: Vec<Pairing>,
+    pub spotlight_countries: Vec<(String, String, Vec<String>)>, // (country, emoji, facts)
+    pub session_picks: HashMap<String, String>, // user_id -> featured country picked this session
+    pub spotlight_planner: SpotlightPlanner,
+    // Country -> active user ids. Derived from `users`, so it isn't saved in
+    // snapshots and `restore_state` rebuilds it
+    #[serde(skip)]
+    pub availability: AvailabilityIndex,
+    pub quiz_progress: HashMap<String, QuizProgress>, // user_id -> quiz record
+    pub icebreaker_history: HashMap<String, Vec<String>>, // user_id -> icebreaker ids already asked
+}
+
+// The whole store, for persistence: a consistent copy taken under the lock
+pub fn export_state() -> PingPairState {
+    read(PingPairState::clone).unwrap_or_default()
+}
+
+// Replace the store's data, e.g. with a snapshot restored after an upgrade.
+// The clock and RNG stay those given to `initialize_state`.
+pub fn restore_state(mut state: PingPairState) -> Result<(), String> {
+    state.availability = AvailabilityIndex::rebuild(state.users.values());
+    let mut guard = store();
+    let store = guard.as_mut().ok_or("The store is not initialized")?;
+    store.state = state;
+    Ok(())
+}
+
+// User management
+pub fn get_user(user_id: &str) -> Option<UserProfile> {
+    read(|state| state.users.get(user_id).cloned()).flatten()
+}
+
+pub fn create_user(user_id: Principal, username: String) -> UserProfile {
+    let profile = UserProfile {
+        user_id,
+        name: username,
+        country: UNKNOWN_COUNTRY.to_string(),
+        interests: Vec::new(),
+        bio: "".to_string(),
+        net_worth: 5, // Starting points
+        badges: Vec::new(),
+        countries_visited: Vec::new(),
+        timezone: "UTC".to_string(),
+        languages: Vec::new(),
+        language_exchange: false,
+        join_date: time(),
+        active: true,
+        locale: DEFAULT_LOCALE.to_string(),
+    };
+    
+    update_user(&user_id.to_text(), profile.clone());
+    profile
+}
+
+pub fn get_or_create_user(user_id: Principal, username: &str) -> UserProfile {
+    get_user(&user_id.to_text()).unwrap_or_else(|| create_user(user_id, username.to_string()))
+}
+
+pub fn get_users() -> Vec<UserProfile> {
+    read(|state| state.users.values().cloned().collect()).unwrap_or_default()
+}
+
+pub fn update_user(user_id: &str, profile: UserProfile) {
+    write(|state| {
+        state.availability.update(state.users.get(user_id), Some(&profile));
+        state.users.insert(user_id.to_string(), profile);
+    });
+}
+
+// The locale to reply in; users we don't know yet get the default
+pub fn get_user_locale(user_id: &str) -> String {
+    get_user(user_id)
+        .map(|user| user.locale)
+        .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
+// Pause or resume matching for a user
+pub fn set_user_active(user_id: &str, active: bool) -> Option<UserProfile> {
+    write(|state| {
+        let mut profile = state.users.get(user_id)?.clone();
+        profile.active = active;
+        state.availability.update(state.users.get(user_id), Some(&profile));
+        state.users.insert(user_id.to_string(), profile.clone());
+        Some(profile)
+    })
+    .flatten()
+}
+
+pub fn delete_user(user_id: &str) -> Option<UserProfile> {
+    write(|state| {
+        let profile = state.users.remove(user_id)?;
+        state.availability.update(Some(&profile), None);
+        Some(profile)
+    })
+    .flatten()
+}
+
+// Countries with at least one active user
+pub fn get_available_countries() -> Vec<String> {
+    read(|state| state.availability.countries().cloned().collect()).unwrap_or_default()
+}
+
+pub fn get_availability() -> AvailabilityIndex {
+    read(|state| state.availability.clone()).unwrap_or_default()
+}
+
+// Pairing management. Fails when either user is already in an active
+// pairing.
+pub fn create_pairing(user1: &str, user2: &str, country: &str) -> Result<Pairing, String> {
+    insert_pairing(user1, user2, country, None)
+}
+
+pub fn create_exchange_pairing(user1: &str, user2: &str, country: &str, exchange: LanguageExchange) -> Result<Pairing, String> {
+    insert_pairing(user1, user2, country, Some(exchange))
+}
+
+fn is_paired(state: &PingPairState, user: Principal) -> bool {
+    state.active_pairings.values().any(|pairing| pairing.user1 == user || pairing.user2 == user)
+}
+
+// Pairing ids are timestamps, suffixed when several pairings share a tick
+fn next_pairing_id(state: &PingPairState, now: u64) -> String {
+    let id = format!("pairing-{}", now);
+    let taken = |id: &String| {
+        state.active_pairings.contains_key(id) || state.completed_pairings.iter().any(|pairing| pairing.id == *id)
+    };
+
+    if !taken(&id) {
+        return id;
+    }
+    (1..)
+        .map(|n| format!("{}-{}", id, n))
+        .find(|candidate| !taken(candidate))
+        .expect("a free pairing id")
+}
+
+fn insert_pairing(user1: &str, user2: &str, country: &str, exchange: Option<LanguageExchange>) -> Result<Pairing, String> {
+    let principal = |id: &str| Principal::from_text(id).map_err(|e| format!("Invalid user id {:?}: {}", id, e));
+    let now = time();
+    let mut pairing = Pairing {
+        id: String::new(),
+        user1: principal(user1)?,
+        user2: principal(user2)?,
+        country: country.to_string(),
+        date_created: now,
+        status: PairingStatus::Active,
+        exchange,
+        meeting_link: None,
+        meeting_time: None,
+        schedule_sequence: 0,
+        icebreakers: select_icebreakers(
+            &[user1, user2],
+            catalogue::catalogue().get(country).map(|country| country.name.as_str()),
+            &shared_interests(user1, user2),
+            icebreakers::PER_PAIRING,
+        ),
+    };
+    
+    // Checked and inserted under one lock, so two commands can neither pair
+    // the same user twice nor claim the same id
+    write(|state| {
+        if let Some(user) = [pairing.user1, pairing.user2].into_iter().find(|user| is_paired(state, *user)) {
+            return Err(format!("{} is already paired", user.to_text()));
+        }
+        pairing.id = next_pairing_id(state, now);
+        record_icebreakers(state, &[user1, user2], &pairing.icebreakers);
+        state.active_pairings.insert(pairing.id.clone(), pairing.clone());
+        Ok(())
+    })
+    .unwrap_or_else(|| Err("The store is not initialized".to_string()))?;
+    metrics::PAIRINGS.inc(&[("event", "created")]);
+    
+    Ok(pairing)
+}
+
+fn shared_interests(user1: &str, user2: &str) -> Vec<String> {
+    match (get_user(user1), get_user(user2)) {
+        (Some(first), Some(second)) => first.interests
+            .into_iter()
+            .filter(|interest| second.interests.iter().any(|other| other.eq_ignore_ascii_case(interest)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Icebreakers none of `users` has seen yet; see `record_icebreakers`
+fn select_icebreakers(users: &[&str], country: Option<&str>, shared: &[String], count: usize) -> Vec<IcebreakerQuestion> {
+    let seen: HashSet<String> = match read(|state| {
+        users
+            .iter()
+            .filter_map(|user| state.icebreaker_history.get(*user))
+            .flatten()
+            .cloned()
+            .collect()
+    }) {
+        Some(seen) => seen,
+        None => return Vec::new(),
+    };
+    with_rng(|rng| icebreakers::bank().select(country, shared, &seen, count, rng))
+}
+
+// Add icebreakers to each user's history so they aren't asked again
+fn record_icebreakers(state: &mut PingPairState, users: &[&str], picked: &[IcebreakerQuestion]) {
+    for user in users {
+        state.icebreaker_history
+            .entry(user.to_string())
+            .or_default()
+            .extend(picked.iter().map(|question| question.id.clone()));
+    }
+}
+
+// A fresh icebreaker for the user's current pairing (or the session's
+// spotlight when unpaired), never one they've been given before
+pub fn next_icebreaker(user_id: &str) -> Option<IcebreakerQuestion> {
+    let (country, shared) = match get_active_pairing_for(user_id) {
+        Some(pairing) => {
+            let partner = if pairing.user1.to_text() == user_id { pairing.user2 } else { pairing.user1 };
+            (Some(pairing.country), shared_interests(user_id, &partner.to_text()))
+        }
+        None => (get_current_session().and_then(|session| session.featured_countries.first().cloned()), Vec::new()),
+    };
+    let country = country.and_then(|name| catalogue::catalogue().get(&name).map(|country| country.name.as_str()));
+
+    let icebreaker = select_icebreakers(&[user_id], country, &shared, 1).pop()?;
+    write(|state| record_icebreakers(state, &[user_id], std::slice::from_ref(&icebreaker)));
+    Some(icebreaker)
+}
+
+pub fn get_active_pairings() -> Vec<Pairing> {
+    read(|state| state.active_pairings.values().cloned().collect()).unwrap_or_default()
+}
+
+pub fn set_meeting_link(pairing_id: &str, link: MeetingLink) -> Option<Pairing> {
+    write(|state| {
+        let pairing = state.active_pairings.get_mut(pairing_id)?;
+        pairing.meeting_link = Some(link);
+        Some(pairing.clone())
+    })
+    .flatten()
+}
+
+// Agree a meeting time (nanoseconds); rescheduling bumps the sequence
+pub fn schedule_pairing(pairing_id: &str, meeting_time: u64) -> Option<Pairing> {
+    write(|state| {
+        let pairing = state.active_pairings.get_mut(pairing_id)?;
+        if pairing.meeting_time.is_some() {
+            pairing.schedule_sequence += 1;
+        }
+        pairing.meeting_time = Some(meeting_time);
+        Some(pairing.clone())
+    })
+    .flatten()
+}
+
+// Look up a pairing whether it's active or archived
+pub fn get_pairing(pairing_id: &str) -> Option<Pairing> {
+    read(|state| {
+        state.active_pairings
+            .get(pairing_id)
+            .or_else(|| state.completed_pairings.iter().find(|pairing| pairing.id == pairing_id))
+            .cloned()
+    })
+    .flatten()
+}
+
+pub fn get_active_pairing_for(user_id: &str) -> Option<Pairing> {
+    let principal = Principal::from_text(user_id).ok()?;
+    read(|state| {
+        state.active_pairings
+            .values()
+            .find(|pairing| pairing.user1 == principal || pairing.user2 == principal)
+            .cloned()
+    })
+    .flatten()
+}
+
+// Completed and cancelled pairings
+pub fn get_past_pairings() -> Vec<Pairing> {
+    read(|state| state.completed_pairings.clone()).unwrap_or_default()
+}
+
+pub fn cancel_pairing(pairing_id: &str) -> Option<Pairing> {
+    let pairing = end_pairing(pairing_id, PairingStatus::Cancelled)?;
+    metrics::PAIRINGS.inc(&[("event", "cancelled")]);
+    Some(pairing)
+}
+
+// Archive a pairing that met: both partners have now visited its country
+// and earn `strix`
+pub fn complete_pairing(pairing_id: &str, strix: u32) -> Option<Pairing> {
+    let pairing = write(|state| {
+        let pairing = archive_pairing(state, pairing_id, PairingStatus::Completed)?;
+        for user_id in [pairing.user1, pairing.user2] {
+            if let Some(user) = state.users.get_mut(&user_id.to_text()) {
+                if !user.countries_visited.contains(&pairing.country) {
+                    user.countries_visited.push(pairing.country.clone());
+                }
+                user.net_worth += strix;
+            }
+        }
+        Some(pairing)
+    })
+    .flatten()?;
+    metrics::PAIRINGS.inc(&[("event", "completed")]);
+    Some(pairing)
+}
+
+// Move an active pairing to the archive with its final status
+fn end_pairing(pairing_id: &str, status: PairingStatus) -> Option<Pairing> {
+    write(|state| archive_pairing(state, pairing_id, status)).flatten()
+}
+
+fn archive_pairing(state: &mut PingPairState, pairing_id: &str, status: PairingStatus) -> Option<Pairing> {
+    let mut pairing = state.active_pairings.remove(pairing_id)?;
+    // Calendars only apply a cancellation that comes with a higher SEQUENCE
+    if status == PairingStatus::Cancelled && pairing.meeting_time.is_some() {
+        pairing.schedule_sequence += 1;
+    }
+    pairing.status = status;
+    state.completed_pairings.push(pairing.clone());
+    Some(pairing)
+}
+
+// Session management
+pub fn get_current_session() -> Option<Session> {
+    read(|state| state.current_session.clone()).flatten()
+}
+
+pub fn get_session_pick(user_id: &str) -> Option<String> {
+    read(|state| state.session_picks.get(user_id).cloned()).flatten()
+}
+
+pub fn get_session_pick_count() -> usize {
+    read(|state| state.session_picks.len()).unwrap_or(0)
+}
+
+pub fn set_session_pick(user_id: &str, country: &str) {
+    write(|state| state.session_picks.insert(user_id.to_string(), country.to_string()));
+}
+
+pub fn get_spotlight_countries() -> Vec<(String, String, Vec<String>)> {
+    read(|state| state.spotlight_countries.clone()).unwrap_or_default()
+}
+
+pub fn create_new_session() -> Session {
+    let now = time();
+    let mut session = Session {
+        id: format!("session-{}", now),
+        date: now,
+        featured_countries: Vec::new(),
+        pairings: Vec::new(),
+    };
+    
+    write(|state| {
+        session.featured_countries = next_featured_countries(state, DEFAULT_COMMUNITY, 3);
+
+        // Archive previous session if exists
+        if let Some(prev_session) = state.current_session.take() {
+            state.past_sessions.push(prev_session);
+        }
+        
+        // Set new session; picks only apply to the session they were made in
+        state.current_session = Some(session.clone());
+        state.session_picks.clear();
+    });
+    
+    session
+}
+
+pub fn get_quiz_progress(user_id: &str) -> QuizProgress {
+    read(|state| state.quiz_progress.get(user_id).cloned())
+        .flatten()
+        .unwrap_or_default()
+}
+
+pub fn set_quiz_progress(user_id: &str, progress: QuizProgress) {
+    write(|state| state.quiz_progress.insert(user_id.to_string(), progress));
+}
+
+// Countries where active members live, which the planner features first
+fn member_countries(state: &PingPairState) -> HashSet<String> {
+    state.availability.countries().cloned().collect()
+}
+
+// Helper functions
+fn next_featured_countries(state: &mut PingPairState, community: &str, count: usize) -> Vec<String> {
+    let active = member_countries(state);
+    state.spotlight_planner.next_countries(community, catalogue::catalogue().all(), &active, count)
+}
+
+// Next country and facts to spotlight at ping time for a community
+pub fn next_spotlight(community: &str, facts: usize) -> Option<Spotlight> {
+    write(|state| {
+        let active = member_countries(state);
+        state.spotlight_planner.next_spotlight(community, catalogue::catalogue().all(), &active, facts)
+    })
+    .flatten()
+}
+
+// Initialize spotlight countries from the country catalogue
+pub fn initialize_spotlight_countries() {
+    let countries = catalogue::catalogue()
+        .all()
+        .iter()
+        .map(|country| (country.name.clone(), country.flag.clone(), country.fun_facts.clone()))
+        .collect();
+    
+    write(|state| state.spotlight_countries = countries);
+}
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    // Tests touching the global store hold this guard: it serialises them
+    // and starts each from an empty store on the given clock and seed.
+    pub(crate) fn fresh_state(clock: Arc<dyn Clock>, seed: u64) -> MutexGuard<'static, ()> {
+        let guard = LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *store() = None;
+        initialize_state(clock, Box::new(SeededRng::new(seed)));
+        guard
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::meeting;
+    use crate::model::clock::FakeClock;
+    use std::time::Duration;
+
+    // 2026-10-19T09:00:00Z
+    const START: u64 = 1_792_400_400 * 1_000_000_000;
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn run_sessions(seed: u64, days: usize) -> Vec<Session> {
+        let clock = Arc::new(FakeClock::new(START));
+        let _guard = testing::fresh_state(clock.clone(), seed);
+
+        (0..days)
+            .map(|_| {
+                let session = create_new_session();
+                clock.advance(DAY);
+                session
+            })
+            .collect()
+    }
+
+    #[test]
+    fn daily_sessions_rotate_without_repeats() {
+        let sessions = run_sessions(1, 5);
+
+        for pair in sessions.windows(2) {
+            assert_eq!(pair[1].date - pair[0].date, DAY.as_nanos() as u64);
+        }
+        let mut featured: Vec<&String> = sessions.iter().flat_map(|session| &session.featured_countries).collect();
+        featured.sort();
+        featured.dedup();
+        assert_eq!(featured.len(), 15);
+    }
+
+    #[test]
+    fn same_seed_same_sessions() {
+        let countries = |sessions: Vec<Session>| -> Vec<Vec<String>> {
+            sessions.into_iter().map(|session| session.featured_countries).collect()
+        };
+
+        assert_eq!(countries(run_sessions(7, 3)), countries(run_sessions(7, 3)));
+        assert_ne!(countries(run_sessions(7, 3)), countries(run_sessions(8, 3)));
+    }
+
+    #[test]
+    fn meeting_links_expire_with_the_clock() {
+        let clock = Arc::new(FakeClock::new(START));
+        let _guard = testing::fresh_state(clock.clone(), 1);
+
+        let user1 = Principal::from_slice(&[1]).to_text();
+        let user2 = Principal::from_slice(&[2]).to_text();
+        let pairing = create_pairing(&user1, &user2, "Kenya").unwrap();
+        let user3 = Principal::from_slice(&[3]).to_text();
+        let user4 = Principal::from_slice(&[4]).to_text();
+        let twin = create_pairing(&user3, &user4, "Kenya").unwrap();
+        assert_ne!(pairing.id, twin.id);
+        assert!(create_pairing(&user1, &user3, "Kenya").is_err());
+        assert!(create_pairing("not-a-principal", &user3, "Kenya").unwrap_err().contains("Invalid user id"));
+
+        let link = meeting::ensure_link(&pairing);
+        clock.advance(DAY);
+        assert_eq!(meeting::ensure_link(&get_pairing(&pairing.id).unwrap()), link);
+
+        clock.advance(DAY * 3);
+        let renewed = meeting::ensure_link(&get_pairing(&pairing.id).unwrap());
+        assert_ne!(renewed.url, link.url);
+        assert_eq!(renewed.expires_at, clock.now() + 4 * DAY.as_nanos() as u64);
+    }
+
+    #[test]
+    fn availability_index_matches_a_full_rebuild() {
+        let _guard = testing::fresh_state(Arc::new(FakeClock::new(START)), 3);
+        let mut rng = SeededRng::new(99);
+        let countries = ["Kenya", "Japan", "Brazil", UNKNOWN_COUNTRY];
+        let ids: Vec<String> = (1..=12u8).map(|i| Principal::from_slice(&[i]).to_text()).collect();
+
+        for _ in 0..500 {
+            let id = &ids[rng.below(ids.len())];
+            match rng.below(4) {
+                0 => {
+                    get_or_create_user(Principal::from_text(id).unwrap(), "member");
+                }
+                1 => {
+                    if let Some(mut user) = get_user(id) {
+                        user.country = countries[rng.below(countries.len())].to_string();
+                        update_user(id, user);
+                    }
+                }
+                2 => {
+                    set_user_active(id, rng.below(2) == 0);
+                }
+                _ => {
+                    delete_user(id);
+                }
+            }
+
+            let users = get_users();
+            assert_eq!(get_availability(), AvailabilityIndex::rebuild(&users));
+        }
+        assert!(!get_available_countries().contains(&UNKNOWN_COUNTRY.to_string()));
+    }
+
+    #[test]
+    fn pairings_get_icebreakers_that_never_repeat() {
+        let _guard = testing::fresh_state(Arc::new(FakeClock::new(START)), 5);
+        let user1 = Principal::from_slice(&[1]).to_text();
+        let user2 = Principal::from_slice(&[2]).to_text();
+        create_user(Principal::from_slice(&[1]), "amina".to_string());
+        create_user(Principal::from_slice(&[2]), "kenji".to_string());
+
+        let pairing = create_pairing(&user1, &user2, "Kenya").unwrap();
+        assert_eq!(pairing.icebreakers.len(), icebreakers::PER_PAIRING);
+        assert_eq!(pairing.icebreakers[0].country.as_deref(), Some("Kenya"));
+
+        let mut seen: HashSet<String> = pairing.icebreakers.iter().map(|icebreaker| icebreaker.id.clone()).collect();
+        while let Some(icebreaker) = next_icebreaker(&user1) {
+            assert!(seen.insert(icebreaker.id.clone()), "repeated {}", icebreaker.id);
+            assert!(!icebreaker.question.contains("{country}"));
+        }
+        assert!(seen.len() > 50);
+    }
+}
@@ -0,0 +1,220 @@
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// User Profile
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct UserProfile {
+    pub user_id: Principal,
+    pub name: String,
+    pub country: String,
+    pub interests: Vec<String>,
+    pub bio: String,
+    pub net_worth: u32,
+    pub badges: Vec<Badge>,
+    pub countries_visited: Vec<String>,
+    pub timezone: String,
+    pub languages: Vec<LanguageSkill>,
+    pub language_exchange: bool,
+    pub join_date: u64,
+    // False while the user has paused matching
+    pub active: bool,
+    // Locale code the bot replies in, see `model::i18n`
+    pub locale: String,
+}
+
+// Language Skills
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize, PartialEq)]
+pub struct LanguageSkill {
+    pub language: String,
+    pub proficiency: Proficiency,
+}
+
+#[derive(Clone, Copy, Debug, CandidType, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Proficiency {
+    Beginner,
+    Intermediate,
+    Advanced,
+    Native,
+}
+
+// Country Information
+#[derive(Clone, Debug, PartialEq, CandidType, Serialize, Deserialize)]
+pub struct Country {
+    pub name: String,
+    pub iso2: String,
+    pub iso3: String,
+    // Common and official alternatives, e.g. "USA" or "Holland"
+    pub aliases: Vec<String>,
+    // The name in the country's own languages, e.g. "Deutschland"
+    pub native_names: Vec<String>,
+    pub fun_facts: Vec<String>,
+    pub continent: String,
+    pub flag: String,
+    pub traditions: Vec<String>,
+    pub languages: Vec<String>,
+    pub available: bool,
+    pub population: u64,
+    pub capital: String,
+    pub currency: String,
+}
+
+// Badge System
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct Badge {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub date_earned: u64,
+}
+
+// Pairing Information
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct Pairing {
+    pub id: String,
+    pub user1: Principal,
+    pub user2: Principal,
+    pub country: String,
+    pub date_created: u64,
+    pub status: PairingStatus,
+    pub exchange: Option<LanguageExchange>,
+    pub meeting_link: Option<MeetingLink>,
+    pub meeting_time: Option<u64>,
+    pub schedule_sequence: u32, // bumped on every reschedule so calendars update the event
+    pub icebreakers: Vec<IcebreakerQuestion>,
+}
+
+// Video call link for a pairing, valid until `expires_at` (nanoseconds)
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize, PartialEq)]
+pub struct MeetingLink {
+    pub url: String,
+    pub expires_at: u64,
+}
+
+// Language exchange split for a pairing: each partner teaches their native
+// language for `minutes_per_language` minutes.
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize, PartialEq)]
+pub struct LanguageExchange {
+    pub user1_teaches: String,
+    pub user2_teaches: String,
+    pub minutes_per_language: u32,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize, PartialEq)]
+pub enum PairingStatus {
+    Active,
+    Completed,
+    Cancelled,
+}
+
+// Culture Quiz
+#[derive(Clone, Copy, Debug, CandidType, Serialize, Deserialize, PartialEq, Eq)]
+pub enum QuizTopic {
+    Capital,
+    Currency,
+    Language,
+    Tradition,
+    Continent,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize, PartialEq)]
+pub struct QuizQuestion {
+    pub country: String,
+    pub topic: QuizTopic,
+    pub choices: Vec<String>,
+    pub answer: u32, // index into `choices`
+    pub session_id: Option<String>, // set for session quiz questions
+}
+
+// A user's quiz record. Strix from the quiz is capped per UTC day.
+#[derive(Clone, Debug, Default, CandidType, Serialize, Deserialize, PartialEq)]
+pub struct QuizProgress {
+    pub pending: Option<QuizQuestion>,
+    pub answered: u32,
+    pub correct: u32,
+    pub streak: u32,
+    pub best_streak: u32,
+    pub strix_earned: u32,
+    pub strix_day: u64, // days since the Unix epoch
+    pub strix_today: u32,
+    pub session_id: Option<String>,
+    pub session_answered: Vec<String>, // featured countries already quizzed
+    pub session_correct: u32,
+}
+
+// Session Information
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct Session {
+    pub id: String,
+    pub date: u64,
+    pub featured_countries: Vec<String>,
+    pub pairings: Vec<Pairing>,
+}
+
+// A place on the Strix leaderboard
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize, PartialEq)]
+pub struct LeaderboardEntry {
+    pub rank: u32, // 1 for the highest Strix score
+    pub user_id: Principal,
+    pub name: String,
+    pub country: String,
+    pub net_worth: u32,
+}
+
+// Community-wide counts for dashboards
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize, PartialEq)]
+pub struct BotStats {
+    pub users: u64,
+    pub active_users: u64,
+    pub countries: u64, // with at least one active member
+    pub active_pairings: u64,
+    pub completed_pairings: u64,
+}
+
+// Command Response
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct CommandResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<CustomData>,
+}
+
+// Using a simpler type instead of serde_json::Value for Candid compatibility
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct CustomData {
+    pub value: String,
+}
+
+// State Management
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct PingPairState {
+    pub users: HashMap<Principal, UserProfile>,
+    pub countries: HashMap<String, Country>,
+    pub current_session: Option<Session>,
+    pub past_sessions: Vec<Session>,
+    pub leaderboard: Vec<(Principal, u32)>, // (user_id, net_worth)
+}
+
+// Bot Command Requests
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub enum BotCommand {
+    Map { personal: bool },
+    Pick { country: String },
+    Pair,
+    Bio,
+    Unpair,
+    Done,
+    Info { country: Option<String> },
+    NetWorth,
+    Icebreaker,
+}
+
+// Icebreaker Questions
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize, PartialEq)]
+pub struct IcebreakerQuestion {
+    pub id: String,
+    pub question: String,
+    pub country_specific: bool,
+    pub country: Option<String>, // only asked in pairings about this country
+    pub interests: Vec<String>, // favoured when partners share one of these
+} 
\ No newline at end of file
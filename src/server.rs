@@ -0,0 +1,348 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::{Path, Query, State},
+    http::{HeaderMap, Request, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use oc_bots_sdk::api::command::{BadRequest, CommandHandlerRegistry, CommandResponse};
+use oc_bots_sdk::api::definition::BotDefinition;
+use oc_bots_sdk::oc_api::client::ClientFactory;
+use oc_bots_sdk_offchain::{env, AgentRuntime};
+use serde::Deserialize;
+use serde_json::json;
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+use tower_http::cors::CorsLayer;
+use tower_http::trace::TraceLayer;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tracing::{error, info, info_span, Span};
+
+use crate::commands::{self, pingpair::SUBCOMMAND};
+use crate::config::Config;
+use crate::oc_keys::{KeyRing, OcKeys, NO_KEY};
+use pingpair_core::{api, metrics, model};
+
+// Structure to hold application state
+pub struct AppState {
+    // Swapped on SIGHUP, see `oc_keys`
+    pub oc_keys: Arc<OcKeys>,
+    // Set once the IC agent is built; until then commands get a 503
+    pub commands: OnceLock<CommandHandlerRegistry<AgentRuntime>>,
+    pub readiness: Readiness,
+    config_fingerprint: String,
+}
+
+impl AppState {
+    pub fn new(keys: KeyRing, config_fingerprint: String) -> Self {
+        AppState {
+            oc_keys: Arc::new(OcKeys::new(keys)),
+            commands: OnceLock::new(),
+            readiness: Readiness::default(),
+            config_fingerprint,
+        }
+    }
+}
+
+// Startup steps reported by /readyz. A component is missing until its step
+// has run, then holds the step's outcome.
+#[derive(Default)]
+pub struct Readiness {
+    components: Mutex<BTreeMap<&'static str, Result<(), String>>>,
+}
+
+impl Readiness {
+    pub fn set(&self, component: &'static str, outcome: Result<(), String>) {
+        self.components.lock().unwrap().insert(component, outcome);
+    }
+}
+
+// Components that must be up before the bot takes commands
+const READINESS_COMPONENTS: [&str; 3] = ["config", "state", "ic_agent"];
+
+// Every endpoint of the bot server
+pub fn router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(bot_definition))
+        .route("/bot_definition", get(bot_definition))
+        .route("/execute", post(execute_command))
+        .route("/execute_command", post(execute_command))
+        .route("/calendar/{pairing_id}", get(calendar_invite))
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .route("/version", get(version))
+        .route("/metrics", get(prometheus_metrics))
+        .layer(CorsLayer::permissive())
+        .layer(TraceLayer::new_for_http().make_span_with(request_span))
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+        .with_state(app_state)
+}
+
+// The commands the bot serves, making calls to OpenChat through `runtime`
+pub fn command_registry(runtime: AgentRuntime) -> CommandHandlerRegistry<AgentRuntime> {
+    CommandHandlerRegistry::new(Arc::new(ClientFactory::new(runtime)))
+        .register(commands::pingpair::PingPairCommand)
+}
+
+// Every log line of a request, including those of the command it runs,
+// carries its request id. Headers and query strings are left out: they hold
+// the JWT and calendar link signatures.
+fn request_span(request: &Request<Body>) -> Span {
+    let request_id = request
+        .headers()
+        .get("x-request-id")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    info_span!("request", request_id = %request_id, method = %request.method(), path = %request.uri().path())
+}
+
+// Load the catalogues and set up the services that read the config
+pub fn configure(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    // Load and validate the country catalogue
+    let catalogue = model::catalogue::load(config.countries_file.as_deref())?;
+    info!("Loaded {} countries (catalogue v{})", catalogue.len(), catalogue.version);
+    let icebreakers = model::icebreakers::load(config.icebreakers_file.as_deref())?;
+    info!("Loaded {} icebreakers", icebreakers.len());
+
+    // Configure the video meeting provider and calendar link signing
+    api::meeting::configure(&config.meeting)?;
+    api::calendar::configure(&config.calendar)?;
+
+    // Validate community template overrides
+    api::render::configure(&config.templates)?;
+
+    // Per-user and per-community command limits
+    api::rate_limit::configure(&config.rate_limits)?;
+
+    Ok(())
+}
+
+// Liveness: the process is up and serving
+async fn healthz() -> (StatusCode, HeaderMap, Bytes) {
+    json_response(StatusCode::OK, json!({ "status": "ok" }))
+}
+
+// Readiness, per component; 503 until every component is up
+async fn readyz(State(state): State<Arc<AppState>>) -> (StatusCode, HeaderMap, Bytes) {
+    let mut outcomes = state.readiness.components.lock().unwrap().clone();
+    // The store is checked live rather than trusted from startup
+    outcomes.insert("state", if model::state::is_initialized() {
+        Ok(())
+    } else {
+        Err("not initialized".to_string())
+    });
+
+    let components: serde_json::Map<String, serde_json::Value> = READINESS_COMPONENTS
+        .iter()
+        .map(|&component| {
+            let report = match outcomes.get(component) {
+                Some(Ok(())) => json!({ "ready": true }),
+                Some(Err(error)) => json!({ "ready": false, "error": error }),
+                None => json!({ "ready": false, "error": "starting" }),
+            };
+            (component.to_string(), report)
+        })
+        .collect();
+    let ready = components.values().all(|report| report["ready"] == true);
+
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    json_response(status, json!({ "ready": ready, "components": components }))
+}
+
+async fn version(State(state): State<Arc<AppState>>) -> (StatusCode, HeaderMap, Bytes) {
+    json_response(StatusCode::OK, json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "git_sha": env!("BUILD_GIT_SHA"),
+        "config_fingerprint": state.config_fingerprint,
+    }))
+}
+
+// Prometheus scrape endpoint
+async fn prometheus_metrics() -> (StatusCode, HeaderMap, Bytes) {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        axum::http::header::CONTENT_TYPE,
+        "text/plain; version=0.0.4".parse().unwrap(),
+    );
+    (StatusCode::OK, headers, Bytes::from(metrics::gather()))
+}
+
+fn json_response(status: StatusCode, body: serde_json::Value) -> (StatusCode, HeaderMap, Bytes) {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        axum::http::header::CONTENT_TYPE,
+        "application/json".parse().unwrap(),
+    );
+    (status, headers, Bytes::from(body.to_string()))
+}
+
+// Bot definition endpoint
+async fn bot_definition(State(state): State<Arc<AppState>>) -> (StatusCode, HeaderMap, Bytes) {
+    let commands = match state.commands.get() {
+        Some(commands) => commands.definitions(),
+        None => return (StatusCode::SERVICE_UNAVAILABLE, HeaderMap::new(), Bytes::from("Bot is starting")),
+    };
+    
+    // Ensure each command has permissions set correctly
+    let commands_with_permissions = commands.into_iter()
+        .map(|mut cmd| {
+            if cmd.permissions.is_none() {
+                cmd.permissions = Some(serde_json::json!({
+                    "community": 0,
+                    "chat": 0,
+                    "message": 0
+                }));
+            }
+            cmd
+        })
+        .collect();
+    
+    let definition = BotDefinition {
+        description: "Connect people globally through themed cultural exchange meetups".to_string(),
+        commands: commands_with_permissions,
+        autonomous_config: None,
+    };
+    
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        axum::http::header::CONTENT_TYPE,
+        "application/json".parse().unwrap(),
+    );
+
+    (
+        StatusCode::OK,
+        headers,
+        Bytes::from(serde_json::to_vec(&definition).unwrap()),
+    )
+}
+
+// Command execution endpoint
+async fn execute_command(
+    State(state): State<Arc<AppState>>, 
+    headers: HeaderMap,
+) -> (StatusCode, Bytes) {
+    // Get JWT from x-oc-jwt header
+    let jwt = match headers.get("x-oc-jwt") {
+        Some(jwt_header) => {
+            match jwt_header.to_str() {
+                Ok(jwt) => jwt.to_string(),
+                Err(e) => {
+                    error!("Invalid JWT header value: {}", e);
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        Bytes::from("Invalid JWT header value"),
+                    );
+                }
+            }
+        },
+        None => {
+            error!("No JWT found in x-oc-jwt header");
+            return (
+                StatusCode::BAD_REQUEST,
+                Bytes::from("Missing JWT header"),
+            );
+        }
+    };
+
+    let Some(commands) = state.commands.get() else {
+        error!("Command received before the bot was ready");
+        return (StatusCode::SERVICE_UNAVAILABLE, Bytes::from("Bot is starting"));
+    };
+
+    // Parse command data from the JWT payload. The subcommand stays "none"
+    // when the JWT is rejected before the command runs.
+    let started = Instant::now();
+    let keys = state.oc_keys.current();
+    let (result, subcommand, oc_key) = SUBCOMMAND
+        .scope(Cell::new("none"), async {
+            let now = env::now();
+            let mut result = CommandResponse::BadRequest(BadRequest::AccessTokenInvalid(
+                "No OpenChat public key is valid at this time".to_string(),
+            ));
+            // Try each key in turn: a token signed with another key is
+            // rejected before the command runs
+            for key in keys.valid_at(now / 1000) {
+                result = commands.execute(&jwt, &key.pem, now).await;
+                if !matches!(result, CommandResponse::BadRequest(BadRequest::AccessTokenInvalid(_))) {
+                    return (result, SUBCOMMAND.with(Cell::get), key.id.as_str());
+                }
+            }
+            (result, SUBCOMMAND.with(Cell::get), NO_KEY)
+        })
+        .await;
+    metrics::OC_KEY_VERIFICATIONS.inc(&[("key", oc_key)]);
+
+    let response = match &result {
+        CommandResponse::Success(_) => "success",
+        CommandResponse::BadRequest(_) => "bad_request",
+        CommandResponse::InternalError(_) => "internal_error",
+        CommandResponse::TooManyRequests => "too_many_requests",
+    };
+    metrics::COMMANDS.inc(&[("subcommand", subcommand), ("response", response)]);
+    metrics::COMMAND_DURATION.observe(&[("subcommand", subcommand), ("response", response)], started.elapsed().as_secs_f64());
+    info!(subcommand, response, oc_key, elapsed_ms = started.elapsed().as_millis() as u64, "Command executed");
+    
+    match result {
+        CommandResponse::Success(r) => {
+            (StatusCode::OK, Bytes::from(serde_json::to_vec(&r).unwrap()))
+        }
+        CommandResponse::BadRequest(r) => {
+            error!("Bad request: {:?}", r);
+            (
+                StatusCode::BAD_REQUEST,
+                Bytes::from(serde_json::to_vec(&r).unwrap()),
+            )
+        }
+        CommandResponse::InternalError(err) => {
+            error!("Internal error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Bytes::from(format!("{err:?}")),
+            )
+        }
+        CommandResponse::TooManyRequests => {
+            error!("Too many requests");
+            (StatusCode::TOO_MANY_REQUESTS, Bytes::new())
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CalendarQuery {
+    expires: u64,
+    sig: String,
+}
+
+// Signed, time-limited iCalendar download for a scheduled pairing
+async fn calendar_invite(
+    Path(pairing_id): Path<String>,
+    Query(query): Query<CalendarQuery>,
+) -> (StatusCode, HeaderMap, Bytes) {
+    let now_secs = model::state::time() / 1_000_000_000;
+    let mut headers = HeaderMap::new();
+
+    if !api::calendar::verify(&pairing_id, query.expires, &query.sig, now_secs) {
+        return (StatusCode::FORBIDDEN, headers, Bytes::from("Invalid or expired calendar link"));
+    }
+
+    let pairing = match model::state::get_pairing(&pairing_id) {
+        Some(pairing) if pairing.meeting_time.is_some() => pairing,
+        _ => return (StatusCode::NOT_FOUND, headers, Bytes::from("No scheduled call for this pairing")),
+    };
+    let country = model::catalogue::catalogue().get(&pairing.country).cloned();
+
+    headers.insert(
+        axum::http::header::CONTENT_TYPE,
+        "text/calendar; charset=utf-8".parse().unwrap(),
+    );
+    headers.insert(
+        axum::http::header::CONTENT_DISPOSITION,
+        "attachment; filename=\"pingpair.ics\"".parse().unwrap(),
+    );
+
+    let ics = api::calendar::render_calendar(&[(pairing, country)], now_secs);
+    (StatusCode::OK, headers, Bytes::from(ics))
+}
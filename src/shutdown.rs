@@ -0,0 +1,36 @@
+use axum::Router;
+use tokio::net::TcpListener;
+use tokio::signal;
+use tokio::sync::watch;
+use tracing::{error, info};
+
+// Serve until `shutdown` turns true, then stop accepting connections and
+// return once the in-flight requests have finished
+pub async fn serve(listener: TcpListener, app: Router, mut shutdown: watch::Receiver<bool>) -> std::io::Result<()> {
+    axum::serve(listener, app.into_make_service())
+        .with_graceful_shutdown(async move {
+            let _ = shutdown.wait_for(|stop| *stop).await;
+        })
+        .await
+}
+
+// Resolves on SIGTERM, which Render sends before stopping an instance, or
+// on Ctrl-C
+pub async fn signal() {
+    let terminate = async {
+        #[cfg(unix)]
+        match signal::unix::signal(signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+                return;
+            }
+            Err(e) => error!("Cannot listen for SIGTERM: {}", e),
+        }
+        std::future::pending::<()>().await
+    };
+
+    tokio::select! {
+        _ = signal::ctrl_c() => info!("Received Ctrl-C"),
+        _ = terminate => info!("Received SIGTERM"),
+    }
+}
@@ -0,0 +1,59 @@
+use pingpair_core::model::snapshot;
+use std::fs;
+use std::io::ErrorKind;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+// The off-chain store is kept in memory and saved to `state_file` as a
+// versioned snapshot, the same format the canister keeps across upgrades.
+
+// Replace the store with the saved one, if there is one
+pub fn load(path: &str) -> Result<(), String> {
+    match fs::read(path) {
+        Ok(bytes) => {
+            snapshot::load(&bytes).map_err(|e| format!("{}: {}", path, e))?;
+            info!(path, bytes = bytes.len(), "State loaded");
+            Ok(())
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            info!(path, "No saved state, starting empty");
+            Ok(())
+        }
+        Err(e) => Err(format!("Cannot read {}: {}", path, e)),
+    }
+}
+
+// Write the store next to `path` and rename it into place, so a crash
+// mid-write leaves the previous save intact
+pub fn save(path: &str) -> Result<(), String> {
+    let bytes = snapshot::save()?;
+    let partial = format!("{}.partial", path);
+    fs::write(&partial, &bytes).map_err(|e| format!("Cannot write {}: {}", partial, e))?;
+    fs::rename(&partial, path).map_err(|e| format!("Cannot replace {}: {}", path, e))?;
+    info!(path, bytes = bytes.len(), "State saved");
+    Ok(())
+}
+
+// Save every `every` until `shutdown` turns true. The final save is left to
+// the caller, once requests have drained.
+pub fn spawn_flusher(path: String, every: Duration, mut shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(every);
+        // The first tick is immediate; the store was just loaded
+        ticks.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = ticks.tick() => {
+                    if let Err(e) = save(&path) {
+                        error!("Periodic state save failed: {}", e);
+                    }
+                }
+                _ = shutdown.wait_for(|stop| *stop) => break,
+            }
+        }
+        info!("State flusher stopped");
+    })
+}
@@ -0,0 +1,127 @@
+// Full /pingpair round trips: commands signed by a local OpenChat stand-in
+// go through the bot server's router and command handlers, with the IC
+// agent pointed at a mock.
+
+mod openchat;
+
+use axum::http::StatusCode;
+use openchat::{Command, OpenChat, Scope, TestBot};
+use std::time::{Duration, Instant};
+use PingPair::config::OcPublicKey;
+
+#[tokio::test]
+async fn commands_round_trip_through_the_router() {
+    let openchat = OpenChat::generate();
+    let bot = TestBot::start(&openchat).await;
+
+    let (status, body) = bot.execute(&openchat.jwt(&Command::pingpair("amina", "start", ""))).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(openchat::reply_text(&body).contains("Welcome to PingPair!"), "{}", body);
+
+    // State carries over between commands: the reply language follows the
+    // user's choice
+    let (status, _) = bot.execute(&openchat.jwt(&Command::pingpair("amina", "language", "es"))).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = bot.execute(&openchat.jwt(&Command::pingpair("amina", "", ""))).await;
+    assert!(openchat::reply_text(&body).contains("Menú de ayuda de PingPair"), "{}", body);
+
+    // Replies are ephemeral, so nothing is sent to OpenChat through the IC
+    assert_eq!(bot.ic.calls(), Vec::<String>::new());
+}
+
+#[tokio::test]
+async fn messages_posted_to_the_chat_go_through_the_ic() {
+    let openchat = OpenChat::generate();
+    let bot = TestBot::start(&openchat).await;
+
+    let (status, body) = bot.execute(&openchat.jwt(&Command::echo("amina", "Habari!"))).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(openchat::reply_text(&body).contains("Habari!"), "{}", body);
+
+    // The message is sent after the reply, as a call to the bot API gateway
+    // the token names
+    let call = format!("/canister/{}/call", openchat::principal("local-gateway"));
+    let start = Instant::now();
+    while !bot.ic.calls().iter().any(|made| made.starts_with("POST ") && made.contains(&call)) {
+        assert!(start.elapsed() < Duration::from_secs(10), "no {} in {:?}", call, bot.ic.calls());
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+#[tokio::test]
+async fn commands_work_in_direct_chats_and_communities() {
+    let openchat = OpenChat::generate();
+    let bot = TestBot::start(&openchat).await;
+
+    for scope in [Scope::Direct("kenji-dm"), Scope::Community("japan-club")] {
+        let command = Command { scope, ..Command::pingpair("kenji", "timezone", "Asia/Tokyo") };
+        let (status, body) = bot.execute(&openchat.jwt(&command)).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert!(openchat::reply_text(&body).contains("Asia/Tokyo"), "{}", body);
+    }
+}
+
+#[tokio::test]
+async fn tokens_the_bot_cannot_verify_are_rejected() {
+    let openchat = OpenChat::generate();
+    let impostor = OpenChat::generate();
+    let bot = TestBot::start(&openchat).await;
+
+    let (status, body) = bot.execute(&impostor.jwt(&Command::pingpair("lucia", "start", ""))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert!(body.contains("AccessTokenInvalid"), "{}", body);
+
+    let expired = Command { expires_in: -60, ..Command::pingpair("lucia", "start", "") };
+    let (status, body) = bot.execute(&openchat.jwt(&expired)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert!(body.contains("AccessTokenExpired"), "{}", body);
+
+    let (status, _) = bot.execute("not.a.jwt").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // The rejected commands never reached a handler
+    let (_, metrics) = bot.get("/metrics").await;
+    assert!(metrics.contains(r#"pingpair_commands_total{subcommand="none",response="bad_request"}"#), "{}", metrics);
+}
+
+#[tokio::test]
+async fn rotated_keys_are_accepted_within_their_window() {
+    let current = OpenChat::generate();
+    let next = OpenChat::generate();
+    let retired = OpenChat::generate();
+    let key = |id: &str, openchat: &OpenChat, not_after: Option<&str>| OcPublicKey {
+        id: id.to_string(),
+        pem: Some(openchat.public_key_pem.clone()),
+        not_after: not_after.map(String::from),
+        ..OcPublicKey::default()
+    };
+    let bot = TestBot::with_keys(&current.public_key_pem, vec![
+        key("next", &next, None),
+        key("retired", &retired, Some("2001-01-01T00:00Z")),
+    ]).await;
+
+    for openchat in [&current, &next] {
+        let (status, body) = bot.execute(&openchat.jwt(&Command::pingpair("noa", "stats", ""))).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+    }
+    let (status, _) = bot.execute(&retired.jwt(&Command::pingpair("noa", "stats", ""))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (_, metrics) = bot.get("/metrics").await;
+    assert!(metrics.contains(r#"pingpair_oc_key_verifications_total{key="next"} 1"#), "{}", metrics);
+    assert!(metrics.contains(r#"pingpair_oc_key_verifications_total{key="primary"}"#), "{}", metrics);
+}
+
+#[tokio::test]
+async fn the_bot_is_ready_and_lists_pingpair() {
+    let openchat = OpenChat::generate();
+    let bot = TestBot::start(&openchat).await;
+
+    let (status, body) = bot.get("/bot_definition").await;
+    assert_eq!(status, StatusCode::OK);
+    let definition: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(definition["commands"][0]["name"], "pingpair");
+
+    let (status, body) = bot.get("/readyz").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
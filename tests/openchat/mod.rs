@@ -0,0 +1,252 @@
+// Offline stand-in for OpenChat: signs command JWTs with a local P-256 key,
+// the way OpenChat does before calling a bot's /execute, and plays the IC
+// endpoint the bot's agent would call back into.
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ic_agent::export::Principal;
+use ic_agent::Agent;
+use oc_bots_sdk_offchain::AgentRuntime;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use p256::pkcs8::{EncodePublicKey, LineEnding};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex, Once};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tower::ServiceExt;
+
+use PingPair::commands::echo::Echo;
+use PingPair::config::{Config, OcPublicKey};
+use PingPair::model;
+use PingPair::model::clock::SystemClock;
+use PingPair::model::rng::SeededRng;
+use PingPair::oc_keys::KeyRing;
+use PingPair::server::{self, AppState};
+
+// Test ids are principals, as in OpenChat, built from a short name
+pub fn principal(name: &str) -> String {
+    Principal::from_slice(name.as_bytes()).to_text()
+}
+
+// Where a command was sent from
+pub enum Scope {
+    Group(&'static str),
+    Direct(&'static str),
+    Community(&'static str),
+}
+
+// A command as OpenChat would send it to the bot
+pub struct Command {
+    pub name: &'static str,
+    pub initiator: String,
+    pub scope: Scope,
+    pub args: Vec<(&'static str, String)>,
+    // Seconds from now until the token expires; negative for expired ones
+    pub expires_in: i64,
+}
+
+impl Command {
+    // `/pingpair <subcommand> <args>` typed by `initiator` in a group chat
+    pub fn pingpair(initiator: &str, subcommand: &str, args: &str) -> Self {
+        Command {
+            name: "pingpair",
+            initiator: principal(initiator),
+            scope: Scope::Group("kenya-club"),
+            args: vec![("subcommand", subcommand.to_string()), ("args", args.to_string())],
+            expires_in: 300,
+        }
+    }
+
+    // `/echo <message>`: the example command, which posts its reply to the
+    // chat through the IC rather than answering ephemerally
+    pub fn echo(initiator: &str, message: &str) -> Self {
+        Command {
+            name: "echo",
+            args: vec![("message", message.to_string())],
+            ..Command::pingpair(initiator, "", "")
+        }
+    }
+}
+
+// OpenChat's side of a command: its signing key and the bot it calls
+pub struct OpenChat {
+    signing_key: SigningKey,
+    pub public_key_pem: String,
+    bot: String,
+}
+
+impl OpenChat {
+    // A new signing key, unknown to any bot until it is put in a config
+    pub fn generate() -> Self {
+        let mut secret = [0u8; 32];
+        getrandom::getrandom(&mut secret).unwrap();
+        let signing_key = SigningKey::from_slice(&secret).expect("random scalar is a valid key");
+        let public_key_pem = signing_key.verifying_key().to_public_key_pem(LineEnding::LF).unwrap();
+
+        OpenChat { signing_key, public_key_pem, bot: principal("pingpair-bot") }
+    }
+
+    // The x-oc-jwt OpenChat sends with `command`: ES256 over claims shaped
+    // like the SDK's `BotCommandContext`
+    pub fn jwt(&self, command: &Command) -> String {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+        let scope = match &command.scope {
+            Scope::Group(chat) => json!({ "Chat": { "chat": { "Group": principal(chat) }, "thread": null, "message_id": "1" } }),
+            Scope::Direct(chat) => json!({ "Chat": { "chat": { "Direct": principal(chat) }, "thread": null, "message_id": "1" } }),
+            Scope::Community(community) => json!({ "Community": { "community_id": principal(community) } }),
+        };
+        let args: Vec<Value> = command
+            .args
+            .iter()
+            .map(|(name, value)| json!({ "name": name, "value": { "String": value } }))
+            .collect();
+        let claims = json!({
+            "exp": now + command.expires_in,
+            "claim_type": "BotActionByCommand",
+            "bot_api_gateway": principal("local-gateway"),
+            "bot": self.bot,
+            "scope": scope,
+            "granted_permissions": { "community": 0, "chat": 0, "message": 1 },
+            "command": {
+                "name": command.name,
+                "args": args,
+                "initiator": command.initiator,
+                "meta": null,
+            },
+        });
+
+        let header = URL_SAFE_NO_PAD.encode(json!({ "alg": "ES256", "typ": "JWT" }).to_string());
+        let claims = URL_SAFE_NO_PAD.encode(claims.to_string());
+        let signed = format!("{}.{}", header, claims);
+        let signature: Signature = self.signing_key.sign(signed.as_bytes());
+        format!("{}.{}", signed, URL_SAFE_NO_PAD.encode(signature.to_bytes()))
+    }
+}
+
+// Answers every call the bot's IC agent makes with an error, and records it
+pub struct MockIc {
+    pub url: String,
+    calls: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockIc {
+    pub async fn start() -> Self {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let recorded = calls.clone();
+        let app = Router::new().fallback(move |request: Request<Body>| {
+            let recorded = recorded.clone();
+            async move {
+                recorded.lock().unwrap().push(format!("{} {}", request.method(), request.uri().path()));
+                (StatusCode::SERVICE_UNAVAILABLE, "No canisters on the mock IC")
+            }
+        });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        MockIc { url, calls }
+    }
+
+    // "METHOD path" of every request so far
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+// The catalogues, templates and limits are process-wide, so they are set up
+// once for every test in the binary
+static CONFIGURE: Once = Once::new();
+
+// Tests share the store, so they take turns
+static TURN: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+// The bot server's router, trusting the given OpenChat keys, with its agent
+// pointed at a mock IC
+pub struct TestBot {
+    state: Option<Arc<AppState>>,
+    pub ic: MockIc,
+    _turn: tokio::sync::MutexGuard<'static, ()>,
+}
+
+impl TestBot {
+    // A bot that accepts tokens from `openchat` only
+    pub async fn start(openchat: &OpenChat) -> Self {
+        TestBot::with_keys(&openchat.public_key_pem, Vec::new()).await
+    }
+
+    // A bot with `primary` as oc_public_key and `extra` as oc_public_keys
+    pub async fn with_keys(primary: &str, extra: Vec<OcPublicKey>) -> Self {
+        let turn = TURN.lock().await;
+        let ic = MockIc::start().await;
+        let config = Config {
+            ic_url: ic.url.clone(),
+            oc_public_key: primary.to_string(),
+            oc_public_keys: extra,
+            ..Config::default()
+        };
+        CONFIGURE.call_once(|| {
+            server::configure(&config).unwrap();
+            model::state::initialize_state(Arc::new(SystemClock), Box::new(SeededRng::from_entropy()));
+            model::state::initialize_spotlight_countries();
+        });
+
+        let state = Arc::new(AppState::new(KeyRing::load(&config).unwrap(), config.fingerprint()));
+        let agent = Agent::builder().with_url(ic.url.clone()).build().unwrap();
+        let runtime = AgentRuntime::new(agent, tokio::runtime::Runtime::new().unwrap());
+        // The bot serves /pingpair only; /echo is added so that a command
+        // calling back into OpenChat through the agent is covered too
+        let _ = state.commands.set(server::command_registry(runtime).register(Echo));
+        state.readiness.set("config", Ok(()));
+        state.readiness.set("ic_agent", Ok(()));
+
+        TestBot { state: Some(state), ic, _turn: turn }
+    }
+
+    // Send a request through the router, as the HTTP server would
+    pub async fn request(&self, request: Request<Body>) -> (StatusCode, String) {
+        let router = server::router(self.state.clone().unwrap());
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8_lossy(&body).into_owned())
+    }
+
+    // POST /execute with `jwt`, as OpenChat calls the bot
+    pub async fn execute(&self, jwt: &str) -> (StatusCode, String) {
+        let request = Request::post("/execute").header("x-oc-jwt", jwt).body(Body::empty()).unwrap();
+        self.request(request).await
+    }
+
+    pub async fn get(&self, path: &str) -> (StatusCode, String) {
+        self.request(Request::get(path).body(Body::empty()).unwrap()).await
+    }
+}
+
+impl Drop for TestBot {
+    fn drop(&mut self) {
+        // The SDK's runtime cannot be dropped from async code
+        let state = self.state.take();
+        std::thread::spawn(move || drop(state)).join().unwrap();
+    }
+}
+
+// Every string in a successful /execute response; the reply's text is one
+// of them
+pub fn reply_text(body: &str) -> String {
+    fn strings(value: &Value, out: &mut Vec<String>) {
+        match value {
+            Value::String(text) => out.push(text.clone()),
+            Value::Array(values) => values.iter().for_each(|value| strings(value, out)),
+            Value::Object(map) => map.values().for_each(|value| strings(value, out)),
+            _ => {}
+        }
+    }
+
+    let response: Value = serde_json::from_str(body).unwrap_or_else(|e| panic!("{}: {}", e, body));
+    let mut out = Vec::new();
+    strings(&response["message"], &mut out);
+    out.join("\n")
+}